@@ -6,6 +6,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
     tonic_build::configure()
         .file_descriptor_set_path(out_dir.join("search_descriptor.bin"))
+        // Log entries hold pages or no data at all, and are short-lived.
+        .type_attribute(".search.LogEntry.operation", "#[allow(clippy::large_enum_variant)]")
         .out_dir("./src")
         .compile(&[proto_file], &["proto"])?;
     Ok(())
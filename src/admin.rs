@@ -0,0 +1,102 @@
+use std::env;
+use std::fs::File;
+use std::io::{Read, Write};
+
+use futures::channel::mpsc;
+use futures::SinkExt;
+use tonic::Request;
+use crate::search::{CollectionInfo, CreateCollectionRequest, DocumentChunk, DropCollectionRequest, ExportRequest, ListCollectionsRequest, SnapshotChunk, SnapshotRequest};
+use crate::search::searcher_client::SearcherClient;
+
+mod search {
+    include!("search.rs");
+}
+
+// Address of the server unless `SEARCH_ADDR` is set.
+const DEFAULT_ADDR: &str = "[::1]:50051";
+// Size of the chunks files are uploaded in.
+const CHUNK_SIZE: usize = 1024 * 1024;
+const USAGE: &str = "Usage: admin snapshot <archive> | admin restore <archive> | admin export <jsonl> | admin import <jsonl> \
+| admin collections | admin create-collection <name> | admin drop-collection <name>";
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let addr = env::var("SEARCH_ADDR").unwrap_or_else(|_| DEFAULT_ADDR.to_string());
+    // Snapshots, restores, exports and imports cover the default collection unless it is set.
+    let collection = env::var("SEARCH_COLLECTION").unwrap_or_default();
+    let mut client = SearcherClient::connect(format!("http://{}", addr)).await?;
+    match args.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        ["snapshot", path] => {
+            let mut archive = File::create(path)?;
+            let mut chunks = client.snapshot(Request::new(SnapshotRequest { collection })).await?.into_inner();
+            while let Some(chunk) = chunks.message().await? {
+                archive.write_all(&chunk.data)?;
+            }
+            archive.flush()?;
+            println!("Saved snapshot to {}", path);
+        },
+        ["restore", path] => {
+            let archive = File::open(path)?;
+            let (sender, receiver) = mpsc::channel(1);
+            let upload = tokio::task::spawn_blocking(move || upload(archive, sender, move |data| SnapshotChunk { data, collection: collection.clone() }));
+            let response = client.restore(Request::new(receiver)).await?.into_inner();
+            upload.await??;
+            println!("Restored {} shards up to sequence {} from {}", response.shards, response.sequence, path);
+        },
+        ["export", path] => {
+            let mut pages = File::create(path)?;
+            let mut chunks = client.export(Request::new(ExportRequest { collection })).await?.into_inner();
+            while let Some(chunk) = chunks.message().await? {
+                pages.write_all(&chunk.data)?;
+            }
+            pages.flush()?;
+            println!("Exported pages to {}", path);
+        },
+        ["import", path] => {
+            let pages = File::open(path)?;
+            let (sender, receiver) = mpsc::channel(1);
+            let upload = tokio::task::spawn_blocking(move || upload(pages, sender, move |data| DocumentChunk { data, collection: collection.clone() }));
+            let response = client.import(Request::new(receiver)).await?.into_inner();
+            upload.await??;
+            println!("Imported {} pages from {}", response.pages, path);
+        },
+        ["collections"] => {
+            for collection in client.list_collections(Request::new(ListCollectionsRequest {})).await?.into_inner().collections {
+                print(&collection);
+            }
+        },
+        ["create-collection", name] => {
+            let request = CreateCollectionRequest { name: name.to_string(), options: None };
+            let response = client.create_collection(Request::new(request)).await?.into_inner();
+            print(&response.collection.unwrap_or_default());
+        },
+        ["drop-collection", name] => {
+            client.drop_collection(Request::new(DropCollectionRequest { name: name.to_string() })).await?;
+            println!("Dropped collection {}", name);
+        },
+        _ => return Err(USAGE.into())
+    }
+    Ok(())
+}
+
+fn print(collection: &CollectionInfo) {
+    let options = collection.options.clone().unwrap_or_default();
+    println!("{}: {} operations, {} shards, passages of {} words, crawls of {} pages", collection.name, collection.sequence,
+        options.shards(), options.passage_words(), options.max_pages());
+}
+
+/// Sends the file in chunks, until the server stops receiving them.
+fn upload<T>(mut file: File, mut sender: mpsc::Sender<T>, chunk: impl Fn(Vec<u8>) -> T) -> std::io::Result<()> {
+    loop {
+        let mut data = vec![0; CHUNK_SIZE];
+        let read = file.read(&mut data)?;
+        if read == 0 {
+            return Ok(());
+        }
+        data.truncate(read);
+        if futures::executor::block_on(sender.send(chunk(data))).is_err() {
+            return Ok(());
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! Cache of search responses, valid for one generation of the index reader.
+
+use std::env;
+use std::sync::Mutex;
+
+use lru::LruCache;
+use prost::Message;
+
+use crate::search::{CacheStats, SearchRequest, SearchResponse};
+
+/// Memory bound of the cache in bytes, counting the encoded size of the keys and responses. A
+/// bound of zero disables the cache.
+#[derive(Clone, Debug)]
+pub struct CacheConfig {
+    pub max_bytes: usize,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self { max_bytes: 64 * 1024 * 1024 }
+    }
+}
+
+impl CacheConfig {
+    /// Default bound, overridden by the `SEARCH_CACHE_BYTES` environment variable.
+    pub fn from_env() -> Result<Self, String> {
+        match env::var("SEARCH_CACHE_BYTES") {
+            Ok(value) => Ok(Self { max_bytes: value.parse().map_err(|_| format!("Invalid SEARCH_CACHE_BYTES: {}", value))? }),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+}
+
+struct Entries {
+    responses: LruCache<Vec<u8>, SearchResponse>,
+    // Reader generation the responses were computed on.
+    generation: u64,
+    bytes: usize,
+    stats: CacheStats,
+}
+
+/// Least recently used search responses, keyed on the normalized request.
+pub struct ResultCache {
+    max_bytes: usize,
+    entries: Mutex<Entries>,
+}
+
+/// Encodes the request with the whitespace of its query collapsed and its field boosts sorted, so
+/// that requests asking for the same results share a key. Case is kept, as it tells operators such
+/// as `NOT` from words.
+fn key(request: &SearchRequest) -> Vec<u8> {
+    let mut boosts: Vec<(&String, &f32)> = request.field_boosts.iter().collect();
+    boosts.sort_by(|a, b| a.0.cmp(b.0));
+    let mut key = SearchRequest {
+        query: request.query.split_whitespace().collect::<Vec<_>>().join(" "),
+        field_boosts: Default::default(),
+        ..request.clone()
+    }.encode_to_vec();
+    for (name, boost) in boosts {
+        key.extend(name.as_bytes());
+        key.push(0);
+        key.extend(boost.to_le_bytes());
+    }
+    key
+}
+
+fn entry_size(key: &[u8], response: &SearchResponse) -> usize {
+    key.len() + response.encoded_len()
+}
+
+impl ResultCache {
+    pub fn new(config: CacheConfig) -> Self {
+        Self {
+            max_bytes: config.max_bytes,
+            entries: Mutex::new(Entries {
+                responses: LruCache::unbounded(),
+                generation: 0,
+                bytes: 0,
+                stats: CacheStats::default(),
+            }),
+        }
+    }
+
+    /// Drops the responses computed on another reader generation.
+    fn invalidate(entries: &mut Entries, generation: u64) {
+        if entries.generation != generation {
+            if !entries.responses.is_empty() {
+                entries.stats.invalidations += 1;
+            }
+            entries.responses.clear();
+            entries.bytes = 0;
+            entries.generation = generation;
+        }
+    }
+
+    /// Drops all responses, for changes to the index that do not reload the reader.
+    pub fn clear(&self) {
+        let mut entries = self.entries.lock().unwrap();
+        let generation = entries.generation;
+        Self::invalidate(&mut entries, generation.wrapping_add(1));
+        entries.generation = generation;
+    }
+
+    pub fn get(&self, request: &SearchRequest, generation: u64) -> Option<SearchResponse> {
+        if self.max_bytes == 0 {
+            return None;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        Self::invalidate(&mut entries, generation);
+        let response = entries.responses.get(&key(request)).cloned();
+        match response {
+            Some(_) => entries.stats.hits += 1,
+            None => entries.stats.misses += 1,
+        }
+        response
+    }
+
+    pub fn insert(&self, request: &SearchRequest, generation: u64, response: SearchResponse) {
+        let key = key(request);
+        let size = entry_size(&key, &response);
+        if size > self.max_bytes {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        Self::invalidate(&mut entries, generation);
+        entries.bytes += size;
+        if let Some(previous) = entries.responses.put(key.clone(), response) {
+            entries.bytes -= entry_size(&key, &previous);
+        }
+        while entries.bytes > self.max_bytes {
+            let Some((key, evicted)) = entries.responses.pop_lru() else { break };
+            entries.bytes -= entry_size(&key, &evicted);
+            entries.stats.evictions += 1;
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let entries = self.entries.lock().unwrap();
+        CacheStats {
+            entries: entries.responses.len() as u64,
+            bytes: entries.bytes as u64,
+            ..entries.stats.clone()
+        }
+    }
+}
@@ -0,0 +1,100 @@
+//! Tokenizer for Chinese, Japanese and Korean text, which is not separated by whitespace.
+//! Runs of CJK characters are split into characters and overlapping bigrams, everything else into
+//! words.
+
+use tantivy::tokenizer::{Token, TokenStream, Tokenizer};
+
+/// Tokenizes CJK runs into characters and overlapping character bigrams, and other alphanumeric
+/// runs into words.
+#[derive(Clone, Default)]
+pub struct CjkTokenizer;
+
+/// TokenStream produced by the `CjkTokenizer`.
+pub struct CjkTokenStream {
+    tokens: Vec<Token>,
+    current: Option<usize>,
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x1100..=0x11FF      // Hangul Jamo
+        | 0x3040..=0x30FF    // Hiragana and Katakana
+        | 0x3130..=0x318F    // Hangul Compatibility Jamo
+        | 0x3400..=0x4DBF    // CJK Unified Ideographs Extension A
+        | 0x4E00..=0x9FFF    // CJK Unified Ideographs
+        | 0xAC00..=0xD7AF    // Hangul Syllables
+        | 0xF900..=0xFAFF    // CJK Compatibility Ideographs
+        | 0xFF66..=0xFF9F    // Halfwidth Katakana
+        | 0x20000..=0x2FA1F  // CJK Unified Ideographs Extension B onwards
+    )
+}
+
+fn push(tokens: &mut Vec<Token>, text: &str, offset_from: usize, offset_to: usize) {
+    tokens.push(Token {
+        offset_from,
+        offset_to,
+        position: tokens.len(),
+        text: text[offset_from..offset_to].to_string(),
+        position_length: 1,
+    });
+}
+
+/// Emits each character of a run of CJK characters as a unigram, followed by the bigram it starts,
+/// as Lucene's CJKBigramFilter does with `outputUnigrams`. Unigrams let single-character queries
+/// match, while bigrams rank pages with the adjacent characters first.
+fn push_cjk_run(tokens: &mut Vec<Token>, text: &str, run: &[(usize, char)]) {
+    for (index, (offset, c)) in run.iter().enumerate() {
+        push(tokens, text, *offset, offset + c.len_utf8());
+        if let Some((next, c)) = run.get(index + 1) {
+            push(tokens, text, *offset, next + c.len_utf8());
+        }
+    }
+}
+
+impl Tokenizer for CjkTokenizer {
+    type TokenStream<'a> = CjkTokenStream;
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> CjkTokenStream {
+        let mut tokens = Vec::new();
+        let mut cjk_run: Vec<(usize, char)> = Vec::new();
+        let mut word_start: Option<usize> = None;
+        for (offset, c) in text.char_indices() {
+            if is_cjk(c) {
+                if let Some(start) = word_start.take() {
+                    push(&mut tokens, text, start, offset);
+                }
+                cjk_run.push((offset, c));
+                continue;
+            }
+            if !cjk_run.is_empty() {
+                push_cjk_run(&mut tokens, text, &cjk_run);
+                cjk_run.clear();
+            }
+            if c.is_alphanumeric() {
+                word_start.get_or_insert(offset);
+            } else if let Some(start) = word_start.take() {
+                push(&mut tokens, text, start, offset);
+            }
+        }
+        if let Some(start) = word_start {
+            push(&mut tokens, text, start, text.len());
+        }
+        push_cjk_run(&mut tokens, text, &cjk_run);
+        CjkTokenStream { tokens, current: None }
+    }
+}
+
+impl TokenStream for CjkTokenStream {
+    fn advance(&mut self) -> bool {
+        let next = self.current.map_or(0, |current| current + 1);
+        self.current = Some(next);
+        next < self.tokens.len()
+    }
+
+    fn token(&self) -> &Token {
+        &self.tokens[self.current.unwrap_or_default()]
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.tokens[self.current.unwrap_or_default()]
+    }
+}
@@ -1,5 +1,5 @@
-use tonic::{Request, Response};
-use crate::search::{IndexRequest, IndexResponse, ResponseStatus, SearchRequest, SearchResponse, SearchResult};
+use tonic::{Request, Response, Streaming};
+use crate::search::{CrawlEvent, CrawlEventKind, IndexRequest, ResponseStatus, SearchRequest, SearchResponse, SearchResult};
 use crate::search::searcher_client::SearcherClient;
 
 mod search {
@@ -10,39 +10,56 @@ mod search {
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut client = SearcherClient::connect("http://[::1]:50051").await?;
     let origin_url = "https://en.wikipedia.org/";
-    handle_index_result(client.index(Request::new(IndexRequest {
+    handle_index_progress(client.index_with_progress(Request::new(IndexRequest {
         origin: origin_url.to_string(),
         k: 2,
-    })).await?, origin_url)?;
+        ..Default::default()
+    })).await?.into_inner(), origin_url).await?;
     let query = "wiki";
     handle_query_result(client.search(Request::new(SearchRequest {
-        query: query.to_string()
+        query: query.to_string(),
+        ..Default::default()
     })).await?, query)?;
     Ok(())
 }
 
 fn print(results: &Vec<SearchResult>) {
     for result in results {
-        println!("relevant URL: {}, origin URL: {}, depth: {}", result.relevant_url, result.origin_url, result.depth);
+        println!("relevant URL: {}, title: {}, origin URL: {}, depth: {}, language: {}", result.relevant_url, result.title, result.origin_url, result.depth, result.language);
     }
 }
 
-fn handle_index_result(response: Response<IndexResponse>, origin_url: &str) -> Result<(), String> {
-    match response.get_ref().status() {
-        ResponseStatus::Ok => {
-            println!("Successfully indexed {}", origin_url);
-            Ok(())
-        },
-        ResponseStatus::Error => {
-            Err(format!("Failed to index {}. Error {}", origin_url, response.get_ref().message()))
+async fn handle_index_progress(mut events: Streaming<CrawlEvent>, origin_url: &str) -> Result<(), String> {
+    while let Some(event) = events.message().await.map_err(|e| e.to_string())? {
+        match event.kind() {
+            CrawlEventKind::Finished => {
+                println!("Successfully indexed {}", origin_url);
+                return Ok(());
+            },
+            CrawlEventKind::Failed if event.url == origin_url => {
+                return Err(format!("Failed to index {}. Error {}", origin_url, event.message()));
+            },
+            kind => println!("{:?} {} (depth {}) {}", kind, event.url, event.depth, event.message())
         }
     }
+    Err(format!("Indexing of {} ended without completion", origin_url))
 }
 
 fn handle_query_result(response: Response<SearchResponse>, query: &str) -> Result<(), String> {
     match response.get_ref().status() {
         ResponseStatus::Ok => {
-            println!("Query {} returned results:", query);
+            println!("Query {} returned {} results:", query, response.get_ref().total_hits);
+            print(&response.get_ref().results);
+            if let Some(suggestion) = &response.get_ref().suggestion {
+                println!("Did you mean: {}?", suggestion);
+            }
+            Ok(())
+        },
+        ResponseStatus::Partial => {
+            for failure in &response.get_ref().failed_peers {
+                println!("Peer {} failed. Error {}", failure.peer, failure.message);
+            }
+            println!("Query {} returned {} results from the other peers:", query, response.get_ref().total_hits);
             print(&response.get_ref().results);
             Ok(())
         },
@@ -0,0 +1,74 @@
+//! Diversification of search results, keeping a few results per host or per cluster of pages
+//! with the same content.
+
+use std::collections::HashMap;
+
+use tantivy::{DocId, SegmentReader};
+
+use crate::hash::fnv1a;
+use crate::search::CollapseBy;
+
+/// Hash of a page body, ignoring case and whitespace, shared by the duplicates of the page.
+pub fn content_hash(body: &str) -> u64 {
+    let normalized: String = body.split_whitespace().map(|word| word.to_lowercase() + " ").collect();
+    fnv1a(normalized.as_bytes())
+}
+
+/// Builds the function reading the group of the documents of a segment. Documents without a
+/// group are never collapsed.
+pub fn group_reader(by: CollapseBy, segment_reader: &SegmentReader) -> Box<dyn Fn(DocId) -> Option<String>> {
+    let fast_fields = segment_reader.fast_fields();
+    match by {
+        CollapseBy::Host => {
+            let host = fast_fields.str("host").unwrap();
+            Box::new(move |doc| {
+                let host = host.as_ref()?;
+                let ord = host.term_ords(doc).next()?;
+                let mut value = String::new();
+                host.ord_to_str(ord, &mut value).ok()?;
+                Some(value).filter(|value| !value.is_empty())
+            })
+        }
+        CollapseBy::ContentHash => {
+            let content_hash = fast_fields.u64("content_hash").unwrap();
+            Box::new(move |doc| content_hash.first(doc).map(|hash| hash.to_string()))
+        }
+    }
+}
+
+/// Keeps the first `max_per_group` of the ranked items of each group, up to `limit` items. Each
+/// kept item comes with the number of items of its group that were left out, counted on the
+/// first kept item of the group only.
+pub fn collapse<T>(ranked: Vec<(Option<String>, T)>, max_per_group: usize, limit: usize) -> Vec<(T, u64)> {
+    let mut totals: HashMap<String, u64> = HashMap::new();
+    for group in ranked.iter().filter_map(|(group, _)| group.clone()) {
+        *totals.entry(group).or_default() += 1;
+    }
+    let mut kept: Vec<(Option<String>, T)> = Vec::new();
+    let mut shown: HashMap<String, u64> = HashMap::new();
+    for (group, item) in ranked {
+        if kept.len() == limit {
+            break;
+        }
+        if let Some(group) = &group {
+            let count = shown.entry(group.clone()).or_default();
+            if *count == max_per_group as u64 {
+                continue;
+            }
+            *count += 1;
+        }
+        kept.push((group, item));
+    }
+    let mut reported: Vec<String> = Vec::new();
+    kept.into_iter().map(|(group, item)| {
+        let hidden = match group {
+            Some(group) if !reported.contains(&group) => {
+                let hidden = totals[&group] - shown[&group];
+                reported.push(group);
+                hidden
+            }
+            _ => 0
+        };
+        (item, hidden)
+    }).collect()
+}
@@ -0,0 +1,219 @@
+//! Collections: named search engines, each with its own index directory, index layout and crawler
+//! settings, so that unrelated projects neither share an index nor its ranking statistics.
+
+use std::collections::HashMap;
+use std::io::{BufRead, Read, Write};
+use std::sync::{Arc, RwLock};
+
+use crate::cache::CacheConfig;
+use crate::crawly::{CrawlObserver, CrawlerBuilder};
+use crate::indexer::Indexer;
+use crate::passage::PassageConfig;
+use crate::ranking::RankingConfig;
+use crate::search::{CacheStats, CollectionInfo, CollectionOptions, MoreLikeThisRequest, SearchRequest, SearchResponse, Suggestion};
+use crate::search_engine::{self, Reader, SearchEngine};
+use crate::shard::ShardConfig;
+use crate::snapshot::Manifest;
+
+/// Collection targeted by requests that name none. It always exists.
+pub const DEFAULT_COLLECTION: &str = "default";
+// Longest collection name.
+const MAX_NAME_LENGTH: usize = 64;
+
+/// Limits of the crawls of a collection.
+#[derive(Clone, Debug)]
+pub struct CrawlerConfig {
+    pub max_pages: usize,
+    pub max_concurrent_requests: usize,
+    pub robots: bool,
+}
+
+impl Default for CrawlerConfig {
+    fn default() -> Self {
+        Self { max_pages: 3, max_concurrent_requests: 2, robots: true }
+    }
+}
+
+/// Settings of a collection, fixed at its creation.
+#[derive(Clone, Debug, Default)]
+pub struct CollectionConfig {
+    pub shards: ShardConfig,
+    pub passages: PassageConfig,
+    pub crawler: CrawlerConfig,
+    // Boosts of the default search fields overridden for the searches of the collection.
+    pub field_boosts: HashMap<String, f32>,
+}
+
+impl CollectionConfig {
+    /// These settings, overridden by the options set.
+    pub fn with_options(&self, options: &CollectionOptions) -> Result<Self, String> {
+        let config = Self {
+            shards: ShardConfig { shards: options.shards.map_or(self.shards.shards, |shards| shards as usize) },
+            passages: PassageConfig {
+                words: options.passage_words.map_or(self.passages.words, |words| words as usize),
+                overlap: options.passage_overlap.map_or(self.passages.overlap, |overlap| overlap as usize),
+            },
+            crawler: CrawlerConfig {
+                max_pages: options.max_pages.map_or(self.crawler.max_pages, |pages| pages as usize),
+                max_concurrent_requests: options.max_concurrent_requests.map_or(self.crawler.max_concurrent_requests, |requests| requests as usize),
+                robots: options.robots.unwrap_or(self.crawler.robots),
+            },
+            field_boosts: self.field_boosts.clone().into_iter().chain(options.field_boosts.clone()).collect(),
+        };
+        if config.shards.shards == 0 {
+            return Err("There must be at least one shard".to_string());
+        }
+        if config.passages.words > 0 && config.passages.overlap >= config.passages.words {
+            return Err("Passage overlap must be smaller than the passage size".to_string());
+        }
+        if config.crawler.max_concurrent_requests == 0 {
+            return Err("Crawls must send at least one request at once".to_string());
+        }
+        search_engine::field_boosts(&config.field_boosts)?;
+        Ok(config)
+    }
+
+    pub fn options(&self) -> CollectionOptions {
+        CollectionOptions {
+            shards: Some(self.shards.shards as u32),
+            passage_words: Some(self.passages.words as u32),
+            passage_overlap: Some(self.passages.overlap as u32),
+            max_pages: Some(self.crawler.max_pages as u32),
+            max_concurrent_requests: Some(self.crawler.max_concurrent_requests as u32),
+            robots: Some(self.crawler.robots),
+            field_boosts: search_engine::field_boosts(&self.field_boosts).expect("Field boosts are checked when set"),
+        }
+    }
+}
+
+/// Checks that the name is made of letters, digits, `-` and `_`, so that it is safe in paths and
+/// URLs.
+pub fn check_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.len() > MAX_NAME_LENGTH {
+        return Err(format!("Collection names have 1 to {} characters", MAX_NAME_LENGTH));
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(format!("Invalid collection name {}: only letters, digits, - and _ are allowed", name));
+    }
+    Ok(())
+}
+
+pub struct Collection {
+    name: String,
+    // The number of shards follows the snapshots restored.
+    config: RwLock<CollectionConfig>,
+    // Settings of restored search engines.
+    ranking: RankingConfig,
+    cache: CacheConfig,
+    // Replaced as a whole when a snapshot is restored.
+    search_engine: RwLock<Arc<SearchEngine>>,
+}
+
+impl Collection {
+    pub fn new(name: String, config: CollectionConfig, ranking: RankingConfig, cache: CacheConfig) -> Self {
+        let search_engine = SearchEngine::new(ranking.clone(), config.passages.clone(), cache.clone(), config.shards.clone());
+        Self { name, config: RwLock::new(config), ranking, cache, search_engine: RwLock::new(Arc::new(search_engine)) }
+    }
+
+    pub fn info(&self) -> CollectionInfo {
+        let options = self.config.read().unwrap().options();
+        CollectionInfo {
+            name: self.name.clone(),
+            options: Some(options),
+            sequence: self.search_engine().replication_log().sequence(),
+        }
+    }
+
+    pub fn search_engine(&self) -> Arc<SearchEngine> {
+        self.search_engine.read().unwrap().clone()
+    }
+
+    pub fn snapshot(&self, out: impl Write) -> Result<Manifest, String> {
+        self.search_engine().snapshot(out)
+    }
+
+    /// Replaces the search engine with the one restored from the snapshot archive, provided that
+    /// nothing was written to the current one.
+    pub fn restore(&self, archive: impl Read) -> Result<Manifest, String> {
+        let not_empty = || "Snapshots are restored into servers with an empty index".to_string();
+        if !self.search_engine().is_empty() {
+            return Err(not_empty());
+        }
+        let (search_engine, manifest) = self.load(archive)?;
+        let mut current = self.search_engine.write().unwrap();
+        if !current.is_empty() {
+            return Err(not_empty());
+        }
+        *current = Arc::new(search_engine);
+        drop(current);
+        self.config.write().unwrap().shards = ShardConfig { shards: manifest.shards };
+        Ok(manifest)
+    }
+
+    /// Replaces the search engine with the one restored from the snapshot archive, whatever the
+    /// current one holds, as followers do when the log of their leader no longer reaches them.
+    pub fn replace(&self, archive: impl Read) -> Result<Manifest, String> {
+        let (search_engine, manifest) = self.load(archive)?;
+        *self.search_engine.write().unwrap() = Arc::new(search_engine);
+        self.config.write().unwrap().shards = ShardConfig { shards: manifest.shards };
+        Ok(manifest)
+    }
+
+    /// Restores a search engine from the snapshot archive, with the settings of the collection.
+    fn load(&self, archive: impl Read) -> Result<(SearchEngine, Manifest), String> {
+        let passages = self.config.read().unwrap().passages.clone();
+        SearchEngine::restore(archive, self.ranking.clone(), passages, self.cache.clone())
+    }
+
+    pub fn export(&self, out: impl Write) -> Result<u64, String> {
+        self.search_engine().export(out)
+    }
+
+    pub fn import(&self, input: impl BufRead) -> Result<u64, String> {
+        self.search_engine().import(input)
+    }
+}
+
+impl Indexer for Collection {
+    async fn visit(&self, origin_url: &str, max_depth: u32, observer: Arc<dyn CrawlObserver + Send + Sync>) -> anyhow::Result<()> {
+        let config = self.config.read().unwrap().crawler.clone();
+        let crawler = CrawlerBuilder::new()
+            .with_max_depth(max_depth as usize)
+            .with_max_pages(config.max_pages)
+            .with_max_concurrent_requests(config.max_concurrent_requests)
+            .with_robots(config.robots)
+            .with_observer(observer)
+            .build()?;
+        let search_engine = self.search_engine();
+        crawler.start(origin_url.to_string(), search_engine.as_ref()).await?;
+        search_engine.analyze_links().map_err(|error| anyhow::anyhow!(error))?;
+        Ok(())
+    }
+}
+
+impl Reader for Collection {
+    fn read(&self, request: &SearchRequest) -> Result<SearchResponse, String> {
+        let field_boosts = self.config.read().unwrap().field_boosts.clone();
+        if field_boosts.is_empty() {
+            return self.search_engine().read(request);
+        }
+        // Boosts of the request take precedence over those of the collection.
+        let mut request = request.clone();
+        for (name, boost) in field_boosts {
+            request.field_boosts.entry(name).or_insert(boost);
+        }
+        self.search_engine().read(&request)
+    }
+
+    fn suggest(&self, prefix: &str, limit: usize) -> Result<Vec<Suggestion>, String> {
+        self.search_engine().suggest(prefix, limit)
+    }
+
+    fn more_like_this(&self, request: &MoreLikeThisRequest) -> Result<SearchResponse, String> {
+        self.search_engine().more_like_this(request)
+    }
+
+    fn cache_stats(&self) -> CacheStats {
+        self.search_engine().cache_stats()
+    }
+}
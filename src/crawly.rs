@@ -5,16 +5,19 @@ use anyhow::Result;
 use futures::future::join_all;
 use indexmap::IndexMap;
 pub use mime::Mime;
-use reqwest::header::HeaderValue;
+use reqwest::header::{HeaderValue, CONTENT_TYPE};
 use reqwest::{Client, Url};
 use robotstxt::DefaultMatcher;
-use scraper::{Html, Selector};
+use scraper::{ElementRef, Html, Selector};
 use std::collections::HashSet;
-use std::fmt::Debug;
+use std::fmt::{Debug, Display, Formatter};
 use std::str::FromStr;
+use std::sync::Arc;
+use std::time::SystemTime;
 use tokio::sync::{RwLock, Semaphore};
 use tokio::time::{sleep, Duration};
-use crate::search_engine::Writer;
+use crate::language;
+use crate::search_engine::{Link, Page, Writer};
 
 const USER_AGENT: &str = "CrawlyRustCrawler";
 
@@ -58,9 +61,65 @@ impl Default for CrawlerConfig {
     }
 }
 
+/// Text extracted from the HTML of a page.
+struct PageContent {
+    // Text outside of scripts and styles.
+    text: String,
+    title: String,
+    description: String,
+    headings: Vec<String>,
+    language: String,
+}
+
+/// Reasons for which the crawler decides not to fetch or index a URL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    DepthLimit,
+    PageLimit,
+    AlreadyVisited,
+    RobotsDisallowed,
+    CloudflareChallenge,
+    MimeNotAllowed,
+}
+
+impl Display for SkipReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let reason = match self {
+            SkipReason::DepthLimit => "maximum depth reached",
+            SkipReason::PageLimit => "maximum number of pages reached",
+            SkipReason::AlreadyVisited => "already visited",
+            SkipReason::RobotsDisallowed => "disallowed by robots.txt",
+            SkipReason::CloudflareChallenge => "Cloudflare challenge",
+            SkipReason::MimeNotAllowed => "MIME type not allowed",
+        };
+        write!(f, "{}", reason)
+    }
+}
+
+/// Hooks into the lifecycle of every URL the crawler touches.
+/// All callbacks default to no-ops, so implementors only override what they need.
+pub trait CrawlObserver {
+    /// The URL has been queued for crawling.
+    fn on_scheduled(&self, _url: &Url, _depth: usize) {}
+    /// The URL has been downloaded.
+    fn on_fetched(&self, _url: &Url, _depth: usize, _status: u16, _bytes: usize) {}
+    /// The URL was not fetched or not indexed.
+    fn on_skipped(&self, _url: &Url, _depth: usize, _reason: SkipReason) {}
+    /// The content of the URL has been handed to the writer.
+    fn on_indexed(&self, _url: &Url, _depth: usize) {}
+    /// Crawling the URL failed.
+    fn on_failed(&self, _url: &Url, _depth: usize, _error: &str) {}
+}
+
+/// Observer that ignores all events.
+pub struct NoopObserver;
+
+impl CrawlObserver for NoopObserver {}
+
 /// Builder pattern for `Crawler`. Allows for customizable configurations.
 pub struct CrawlerBuilder {
     config: CrawlerConfig,
+    observer: Arc<dyn CrawlObserver + Send + Sync>,
 }
 
 impl Default for CrawlerBuilder {
@@ -74,6 +133,7 @@ impl CrawlerBuilder {
     pub fn new() -> Self {
         CrawlerBuilder {
             config: CrawlerConfig::default(),
+            observer: Arc::new(NoopObserver),
         }
     }
 
@@ -96,6 +156,7 @@ impl CrawlerBuilder {
     }
 
     /// Define a rate limit delay in seconds.
+    #[allow(dead_code)]
     pub fn with_rate_limit_wait_seconds(mut self, seconds: u64) -> Self {
         self.config.rate_limit_wait_seconds = seconds;
         self
@@ -108,20 +169,28 @@ impl CrawlerBuilder {
     }
 
     /// Set a custom user agent
+    #[allow(dead_code)]
     pub fn with_user_agent<S: AsRef<str>>(mut self, user_agent: S) -> Self {
         self.config.user_agent = user_agent.as_ref().into();
         self
     }
 
     /// Allow only a set of MIMEs
+    #[allow(dead_code)]
     pub fn with_allowed_mimes(mut self, mime_types: Vec<Mime>) -> Self {
         self.config.allowed_mimes = mime_types;
         self
     }
 
+    /// Set an observer notified about crawl events
+    pub fn with_observer(mut self, observer: Arc<dyn CrawlObserver + Send + Sync>) -> Self {
+        self.observer = observer;
+        self
+    }
+
     /// Consumes the builder and returns a configured `Crawler` instance.
     pub fn build(self) -> Result<Crawler> {
-        Crawler::from_config(self.config)
+        Crawler::from_config(self.config, self.observer)
     }
 }
 
@@ -130,30 +199,50 @@ pub struct Crawler {
     config: CrawlerConfig, // Configuration parameters.
     client: Client,        // HTTP client to make web requests.
     robots_cache: RwLock<IndexMap<String, RobotsCache>>, // Cache for `robots.txt` per domain.
+    observer: Arc<dyn CrawlObserver + Send + Sync>, // Notified about crawl events.
 }
 
 impl Crawler {
     /// Initializes the crawler with a given configuration.
-    fn from_config(config: CrawlerConfig) -> Result<Self> {
+    fn from_config(config: CrawlerConfig, observer: Arc<dyn CrawlObserver + Send + Sync>) -> Result<Self> {
         Ok(Self {
             client: Client::builder()
                 .user_agent(config.user_agent.as_str())
                 .build()?,
             robots_cache: RwLock::new(IndexMap::new()),
             config,
+            observer,
         })
     }
 
     /// Initializes a new `Crawler` instance with the default configuration.
+    #[allow(dead_code)]
     pub fn new() -> Result<Self> {
-        Self::from_config(CrawlerConfig::default())
+        Self::from_config(CrawlerConfig::default(), Arc::new(NoopObserver))
+    }
+
+    /// Crawls a URL and reports any error to the observer before propagating it.
+    #[async_recursion::async_recursion]
+    async fn crawl(
+        &self,
+        semaphore: &Semaphore,
+        origin_url: &str,
+        url: Url,
+        depth: usize,
+        visited: &RwLock<HashSet<Url>>,
+        writer: &(dyn Writer + Send + Sync)
+    ) -> Result<()> {
+        let result = self.visit(semaphore, origin_url, url.clone(), depth, visited, writer).await;
+        if let Err(error) = &result {
+            self.observer.on_failed(&url, depth, &error.to_string());
+        }
+        result
     }
 
     /// Asynchronously crawls a URL. Honors `robots.txt`, maintains state about visited URLs,
     /// and manages rate limits and concurrency.
-    #[async_recursion::async_recursion]
     #[tracing::instrument(skip(self, semaphore, visited, writer))]
-    async fn crawl(
+    async fn visit(
         &self,
         semaphore: &Semaphore, // Rate limiting and concurrency management.
         origin_url: &str,
@@ -164,14 +253,21 @@ impl Crawler {
     ) -> Result<()> {
         let permit = semaphore.acquire().await;
         // Recursion base cases.
-        if depth > self.config.max_depth
-            || visited.read().await.len() > self.config.max_pages
-            || visited.read().await.contains(&url)
-        {
+        let limit = if depth > self.config.max_depth {
+            Some(SkipReason::DepthLimit)
+        } else if visited.read().await.len() > self.config.max_pages {
+            Some(SkipReason::PageLimit)
+        } else if visited.read().await.contains(&url) {
+            Some(SkipReason::AlreadyVisited)
+        } else {
+            None
+        };
+        if let Some(reason) = limit {
             tracing::info!(
                 "Reached the limit {{ depth: {depth}, visited: {} }}.",
                 visited.read().await.len()
             );
+            self.observer.on_skipped(&url, depth, reason);
 
             return Ok(());
         }
@@ -207,7 +303,7 @@ impl Crawler {
                     .lines()
                     .filter_map(|line| {
                         if line.contains("Crawl-delay") {
-                            line.split(':').last()?.trim().parse().ok()
+                            line.split(':').next_back()?.trim().parse().ok()
                         } else {
                             None
                         }
@@ -242,6 +338,7 @@ impl Crawler {
                     self.config.user_agent.as_str(),
                     url.as_str(),
                 ) {
+                    self.observer.on_skipped(&url, depth, SkipReason::RobotsDisallowed);
                     return Ok(());
                 }
             }
@@ -254,12 +351,22 @@ impl Crawler {
         // Check if the response is mitigated by Cloudflare and skip it
         if response.headers().get("cf-mitigated") == Some(&HeaderValue::from_str("challenge")?) {
             tracing::debug!("Cloudflare mitigation found, skipping this URL {{ url: {url} }}");
+            self.observer.on_skipped(&url, depth, SkipReason::CloudflareChallenge);
 
             return Ok(());
         }
 
         // Fetch the page content.
+        let status = response.status().as_u16();
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| Mime::from_str(value).ok())
+            .map(|mime| mime.essence_str().to_string());
+        let fetched_at = SystemTime::now();
         let page = response.bytes().await?.to_vec();
+        self.observer.on_fetched(&url, depth, status, page.len());
 
         if !self.config.allowed_mimes.is_empty()
             && infer::get(page.as_slice())
@@ -276,13 +383,50 @@ impl Crawler {
             drop(permit);
 
             visited.write().await.insert(url.clone());
+            self.observer.on_skipped(&url, depth, SkipReason::MimeNotAllowed);
 
             return Ok(());
         }
 
         // Fetch the page content.
         let url_content = String::from_utf8(page)?;
-        writer.write(&url_content.clone(), &url.clone().to_string(), origin_url, depth as u32);
+        let links = Self::extract_links(url_content.as_str())
+            .map(|links| {
+                tracing::debug!(
+                    "Found other sub-URLs {{ len: {}, links: {links:#?} }}",
+                    links.len()
+                );
+
+                links
+            })?;
+        let outlinks: Vec<(Url, String)> = links
+            .into_iter()
+            .filter_map(|(link, anchor_text)| Some((url.join(&link).ok()?, anchor_text)))
+            .filter(|(link, _)| matches!(link.scheme(), "http" | "https"))
+            .map(|(mut link, anchor_text)| {
+                link.set_fragment(None);
+                (link, anchor_text)
+            })
+            .collect();
+        let content = Self::extract_content(url_content.as_str())?;
+        writer.write(&Page {
+            url: url.to_string(),
+            origin_url: origin_url.to_string(),
+            depth: depth as u32,
+            body: url_content,
+            text: content.text,
+            title: content.title,
+            description: content.description,
+            headings: content.headings,
+            language: content.language,
+            content_type: content_type.unwrap_or_else(|| mime::TEXT_HTML.essence_str().to_string()),
+            fetched_at,
+            links: outlinks.iter().map(|(link, anchor_text)| Link {
+                url: link.to_string(),
+                anchor_text: anchor_text.clone(),
+            }).collect(),
+        }).map_err(|error| anyhow::anyhow!(error))?;
+        self.observer.on_indexed(&url, depth);
 
         // Explicitly dropping the permit to free up concurrency slot.
         drop(permit);
@@ -290,26 +434,16 @@ impl Crawler {
         visited.write().await.insert(url.clone());
 
         // Continue crawling by processing extracted links recursively.
+        let mut targets: Vec<Url> = outlinks.into_iter().map(|(link, _)| link).collect();
+        targets.sort();
+        targets.dedup();
         let _ = join_all(
-            Self::extract_links(url_content.as_str())
-                .map(|links| {
-                    tracing::debug!(
-                        "Found other sub-URLs {{ len: {}, links: {links:#?} }}",
-                        links.len()
-                    );
-
-                    links
-                })?
+            targets
                 .into_iter()
-                .filter_map(|link| match url.join(&link) {
-                    Ok(url) => {
-                        if url.domain().unwrap_or_default() == domain {
-                            Some(self.crawl(semaphore, origin_url, url, depth + 1, visited, writer))
-                        } else {
-                            None
-                        }
-                    },
-                    Err(_) => None,
+                .filter(|link| link.domain().unwrap_or_default() == domain)
+                .map(|link| {
+                    self.observer.on_scheduled(&link, depth + 1);
+                    self.crawl(semaphore, origin_url, link, depth + 1, visited, writer)
                 }),
         )
             .await;
@@ -319,18 +453,67 @@ impl Crawler {
         Ok(())
     }
 
-    /// Extracts hyperlinks from given HTML content.
+    /// Extracts hyperlinks from given HTML content, along with their anchor text.
     #[tracing::instrument(skip(content))]
-    fn extract_links(content: &str) -> Result<Vec<String>> {
+    fn extract_links(content: &str) -> Result<Vec<(String, String)>> {
         let document = Html::parse_document(content);
         let selector = Selector::parse("a").map_err(|error| anyhow::anyhow!("{:?}", error))?;
 
         Ok(document
             .select(&selector)
-            .filter_map(|element| element.value().attr("href").map(|href| href.to_string()))
+            .filter_map(|element| {
+                let href = element.value().attr("href")?;
+                let anchor_text = element.text().flat_map(str::split_whitespace).collect::<Vec<_>>().join(" ");
+                Some((href.to_string(), anchor_text))
+            })
             .collect())
     }
 
+    /// Extracts the visible text, title, description and headings of given HTML content, and
+    /// detects its language from its visible text, using the `lang` attribute of the document as
+    /// a hint.
+    #[tracing::instrument(skip(content))]
+    fn extract_content(content: &str) -> Result<PageContent> {
+        let document = Html::parse_document(content);
+        let select = |selectors: &str| {
+            Selector::parse(selectors).map_err(|error| anyhow::anyhow!("{:?}", error))
+        };
+        let element_text = |element: ElementRef| {
+            element.text().flat_map(str::split_whitespace).collect::<Vec<_>>().join(" ")
+        };
+        let title = document.select(&select("title")?).next().map(element_text).unwrap_or_default();
+        let description = document
+            .select(&select("meta[name=description]")?)
+            .find_map(|element| element.value().attr("content"))
+            .map(|description| description.trim().to_string())
+            .unwrap_or_default();
+        let headings = document
+            .select(&select("h1, h2, h3, h4, h5, h6")?)
+            .map(element_text)
+            .filter(|heading| !heading.is_empty())
+            .collect();
+        let hint = document.root_element().value().attr("lang");
+        let text = document
+            .root_element()
+            .descendants()
+            .filter_map(|node| {
+                let text = node.value().as_text()?;
+                let parent = node.parent()?.value().as_element()?;
+                (!matches!(parent.name(), "script" | "style" | "noscript")).then_some(&**text)
+            })
+            .flat_map(str::split_whitespace)
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        Ok(PageContent {
+            language: language::detect(&text, hint),
+            text,
+            title,
+            description,
+            headings,
+        })
+    }
+
     /// Initiates the crawling process from a specified root URL.
     ///
     /// Returns a map of visited URLs and their corresponding HTML content.
@@ -341,7 +524,8 @@ impl Crawler {
         let semaphore = Semaphore::new(self.config.max_concurrent_requests);
         let visited = RwLock::new(HashSet::new());
 
-        self.crawl(&semaphore, &root_url.clone().to_string(), root_url, 0, &visited, writer)
+        self.observer.on_scheduled(&root_url, 0);
+        self.crawl(&semaphore, root_url.as_ref(), root_url.clone(), 0, &visited, writer)
             .await?;
 
         Ok(())
@@ -0,0 +1,55 @@
+//! Dense vector representations of texts, for semantic search.
+
+use std::collections::HashMap;
+
+use crate::hash::fnv1a;
+
+// Dimensions of the vectors of the hashing embedder.
+const DEFAULT_DIMENSIONS: usize = 256;
+// Length of the character n-grams hashed alongside words, which let inflected forms and typos
+// share features.
+const NGRAM_LENGTH: usize = 3;
+
+/// Maps texts to vectors whose dot product is higher for texts with closer meanings.
+pub trait Embedder {
+    /// Embeds the text into a vector of unit length, or of zeros if the text has no features.
+    fn embed(&self, text: &str) -> Vec<f32>;
+}
+
+/// Embedder running on the CPU without a model: words and their character trigrams are hashed
+/// into the components of the vector, with a hashed sign so that collisions cancel out on
+/// average, and weighted by the logarithm of their frequency.
+pub struct HashingEmbedder {
+    dimensions: usize,
+}
+
+impl Default for HashingEmbedder {
+    fn default() -> Self {
+        Self { dimensions: DEFAULT_DIMENSIONS }
+    }
+}
+
+impl Embedder for HashingEmbedder {
+    fn embed(&self, text: &str) -> Vec<f32> {
+        let mut counts: HashMap<String, u32> = HashMap::new();
+        for word in text.split(|c: char| !c.is_alphanumeric()).filter(|word| !word.is_empty()) {
+            let word = word.to_lowercase();
+            let padded: Vec<char> = format!("<{}>", word).chars().collect();
+            for ngram in padded.windows(NGRAM_LENGTH) {
+                *counts.entry(ngram.iter().collect()).or_default() += 1;
+            }
+            *counts.entry(word).or_default() += 1;
+        }
+        let mut vector = vec![0.0f32; self.dimensions];
+        for (feature, count) in counts {
+            let hash = fnv1a(feature.as_bytes());
+            let sign = if hash >> 63 == 0 { 1.0 } else { -1.0 };
+            vector[(hash % self.dimensions as u64) as usize] += sign * (1.0 + (count as f32).ln());
+        }
+        let norm = vector.iter().map(|component| component * component).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            vector.iter_mut().for_each(|component| *component /= norm);
+        }
+        vector
+    }
+}
@@ -0,0 +1,89 @@
+//! Export and import of the stored pages as JSON Lines, streamed in chunks so that neither side
+//! holds the whole index in memory.
+
+use std::io::{self, BufReader, Read, Write};
+use std::sync::Arc;
+
+use futures::channel::mpsc;
+use futures::SinkExt;
+use tonic::{Status, Streaming};
+
+use crate::collection::Collection;
+use crate::search::DocumentChunk;
+use crate::snapshot::CHUNK_SIZE;
+
+/// Buffers the export into chunks, sent as they fill up.
+struct ChunkWriter {
+    sender: mpsc::Sender<Result<DocumentChunk, Status>>,
+    buffer: Vec<u8>,
+}
+
+impl ChunkWriter {
+    fn send(&mut self) -> io::Result<()> {
+        let data = std::mem::take(&mut self.buffer);
+        futures::executor::block_on(self.sender.send(Ok(DocumentChunk { data, ..Default::default() })))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "The export was cancelled"))
+    }
+}
+
+impl Write for ChunkWriter {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(bytes);
+        if self.buffer.len() >= CHUNK_SIZE {
+            self.send()?;
+        }
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        self.send()
+    }
+}
+
+/// Streams the export of the collection, ending with an error if it fails midway.
+pub fn stream(collection: Arc<Collection>) -> mpsc::Receiver<Result<DocumentChunk, Status>> {
+    let (sender, receiver) = mpsc::channel(1);
+    tokio::task::spawn_blocking(move || {
+        let mut out = ChunkWriter { sender: sender.clone(), buffer: Vec::new() };
+        match collection.export(&mut out) {
+            Ok(pages) => tracing::info!("Exported {} pages", pages),
+            Err(message) => {
+                let _ = futures::executor::block_on(out.sender.send(Err(Status::aborted(message))));
+            }
+        }
+    });
+    receiver
+}
+
+/// Reads the chunks of an import as they arrive.
+struct ChunkReader {
+    chunks: Streaming<DocumentChunk>,
+    chunk: Vec<u8>,
+    position: usize,
+}
+
+impl Read for ChunkReader {
+    fn read(&mut self, bytes: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.chunk.len() {
+            match futures::executor::block_on(self.chunks.message()) {
+                Ok(Some(chunk)) => (self.chunk, self.position) = (chunk.data, 0),
+                Ok(None) => return Ok(0),
+                Err(status) => return Err(io::Error::other(status.message().to_string())),
+            }
+        }
+        let read = bytes.len().min(self.chunk.len() - self.position);
+        bytes[..read].copy_from_slice(&self.chunk[self.position..self.position + read]);
+        self.position += read;
+        Ok(read)
+    }
+}
+
+/// Imports the first chunk, already received, and the chunks streamed after it, returning the
+/// number of pages imported.
+pub async fn import(collection: Arc<Collection>, first: DocumentChunk, chunks: Streaming<DocumentChunk>) -> Result<u64, String> {
+    let input = BufReader::new(ChunkReader { chunks, chunk: first.data, position: 0 });
+    tokio::task::spawn_blocking(move || collection.import(input)).await.map_err(|e| e.to_string())?
+}
@@ -0,0 +1,394 @@
+//! Scoring expressions sent with search requests, such as
+//! `score * 1.0 / (1 + depth) + 0.2 * log(authority)`, evaluated over the fast fields of each
+//! matching document.
+//!
+//! Expressions combine numbers and variables with `+`, `-`, `*`, `/`, `^` and parentheses, and
+//! call the functions `log`, `log10`, `sqrt`, `exp`, `abs`, `min`, `max` and `pow`. `log` is
+//! the natural logarithm.
+
+use tantivy::{DocId, Score, SegmentReader};
+
+use crate::ranking::{self, Signals};
+
+// Longest expression, in bytes. Evaluating an expression recurses as deep as its tree.
+const MAX_LENGTH: usize = 1_000;
+// Deepest nesting of parentheses, function calls, negations and powers, which the parser
+// recurses into.
+const MAX_DEPTH: usize = 32;
+
+/// Values available to expressions.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Variable {
+    // Score of the document after the ranking signals.
+    Score,
+    Depth,
+    Authority,
+    UrlLength,
+    // Days since the document was fetched.
+    AgeDays,
+}
+
+impl Variable {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "score" => Some(Self::Score),
+            "depth" => Some(Self::Depth),
+            "authority" => Some(Self::Authority),
+            "url_length" => Some(Self::UrlLength),
+            "age_days" => Some(Self::AgeDays),
+            _ => None
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Function {
+    Log,
+    Log10,
+    Sqrt,
+    Exp,
+    Abs,
+    Min,
+    Max,
+    Pow,
+}
+
+impl Function {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "log" => Some(Self::Log),
+            "log10" => Some(Self::Log10),
+            "sqrt" => Some(Self::Sqrt),
+            "exp" => Some(Self::Exp),
+            "abs" => Some(Self::Abs),
+            "min" => Some(Self::Min),
+            "max" => Some(Self::Max),
+            "pow" => Some(Self::Pow),
+            _ => None
+        }
+    }
+
+    fn arity(self) -> usize {
+        match self {
+            Self::Min | Self::Max | Self::Pow => 2,
+            _ => 1
+        }
+    }
+
+    fn apply(self, arguments: &[f64]) -> f64 {
+        match self {
+            Self::Log => arguments[0].ln(),
+            Self::Log10 => arguments[0].log10(),
+            Self::Sqrt => arguments[0].sqrt(),
+            Self::Exp => arguments[0].exp(),
+            Self::Abs => arguments[0].abs(),
+            Self::Min => arguments[0].min(arguments[1]),
+            Self::Max => arguments[0].max(arguments[1]),
+            Self::Pow => arguments[0].powf(arguments[1]),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Power,
+}
+
+#[derive(Clone, Debug)]
+enum Node {
+    Number(f64),
+    Variable(Variable),
+    Negate(Box<Node>),
+    Binary(Operator, Box<Node>, Box<Node>),
+    Call(Function, Vec<Node>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Identifier(String),
+    Symbol(char),
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut end = start;
+            while let Some(&(i, c)) = chars.peek() {
+                if !(c.is_ascii_digit() || c == '.') {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            let number = &text[start..end];
+            tokens.push(Token::Number(number.parse().map_err(|_| format!("Invalid number {}", number))?));
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let mut end = start;
+            while let Some(&(i, c)) = chars.peek() {
+                if !(c.is_ascii_alphanumeric() || c == '_') {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            tokens.push(Token::Identifier(text[start..end].to_string()));
+        } else if "+-*/^(),".contains(c) {
+            tokens.push(Token::Symbol(c));
+            chars.next();
+        } else {
+            return Err(format!("Unexpected character {} in scoring expression", c));
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent parser. `^` binds tighter than unary minus, which binds tighter than `*`
+/// and `/`, which bind tighter than `+` and `-`.
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn expect(&mut self, symbol: char) -> Result<(), String> {
+        match self.next() {
+            Some(Token::Symbol(c)) if c == symbol => Ok(()),
+            _ => Err(format!("Expected {} in scoring expression", symbol))
+        }
+    }
+
+    fn sum(&mut self) -> Result<Node, String> {
+        let mut left = self.product()?;
+        loop {
+            let operator = match self.peek() {
+                Some(Token::Symbol('+')) => Operator::Add,
+                Some(Token::Symbol('-')) => Operator::Subtract,
+                _ => return Ok(left)
+            };
+            self.next();
+            left = Node::Binary(operator, Box::new(left), Box::new(self.product()?));
+        }
+    }
+
+    fn product(&mut self) -> Result<Node, String> {
+        let mut left = self.unary()?;
+        loop {
+            let operator = match self.peek() {
+                Some(Token::Symbol('*')) => Operator::Multiply,
+                Some(Token::Symbol('/')) => Operator::Divide,
+                _ => return Ok(left)
+            };
+            self.next();
+            left = Node::Binary(operator, Box::new(left), Box::new(self.unary()?));
+        }
+    }
+
+    /// Every recursion of the parser goes through here, where its depth is bounded.
+    fn unary(&mut self) -> Result<Node, String> {
+        if self.depth == MAX_DEPTH {
+            return Err(format!("Scoring expressions nest at most {} levels deep", MAX_DEPTH));
+        }
+        self.depth += 1;
+        let node = if self.peek() == Some(&Token::Symbol('-')) {
+            self.next();
+            self.unary().map(|operand| Node::Negate(Box::new(operand)))
+        } else {
+            self.power()
+        };
+        self.depth -= 1;
+        node
+    }
+
+    fn power(&mut self) -> Result<Node, String> {
+        let base = self.atom()?;
+        if self.peek() == Some(&Token::Symbol('^')) {
+            self.next();
+            // Right associative: 2 ^ 3 ^ 2 is 2 ^ 9.
+            return Ok(Node::Binary(Operator::Power, Box::new(base), Box::new(self.unary()?)));
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<Node, String> {
+        match self.next() {
+            Some(Token::Number(number)) => Ok(Node::Number(number)),
+            Some(Token::Symbol('(')) => {
+                let expression = self.sum()?;
+                self.expect(')')?;
+                Ok(expression)
+            }
+            Some(Token::Identifier(name)) => {
+                if self.peek() != Some(&Token::Symbol('(')) {
+                    return Variable::parse(&name)
+                        .map(Node::Variable)
+                        .ok_or_else(|| format!("Unknown variable {} in scoring expression", name));
+                }
+                let function = Function::parse(&name)
+                    .ok_or_else(|| format!("Unknown function {} in scoring expression", name))?;
+                self.next();
+                let mut arguments = vec![self.sum()?];
+                while self.peek() == Some(&Token::Symbol(',')) {
+                    self.next();
+                    arguments.push(self.sum()?);
+                }
+                self.expect(')')?;
+                if arguments.len() != function.arity() {
+                    return Err(format!("{} takes {} argument(s) in scoring expression", name, function.arity()));
+                }
+                Ok(Node::Call(function, arguments))
+            }
+            _ => Err("Unexpected end of scoring expression".to_string())
+        }
+    }
+}
+
+/// Values of the variables for one document.
+struct Values {
+    score: f64,
+    signals: Signals,
+}
+
+/// A parsed scoring expression.
+#[derive(Clone, Debug)]
+pub struct Expression {
+    root: Node,
+}
+
+impl Expression {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        if text.len() > MAX_LENGTH {
+            return Err(format!("Scoring expressions are at most {} bytes long", MAX_LENGTH));
+        }
+        let mut parser = Parser { tokens: tokenize(text)?, position: 0, depth: 0 };
+        let root = parser.sum()?;
+        if parser.position < parser.tokens.len() {
+            return Err("Unexpected trailing input in scoring expression".to_string());
+        }
+        Ok(Self { root })
+    }
+
+    /// Builds the function computing the expression for the documents of a segment from their
+    /// score. `now` is in seconds since the Unix epoch. Results that are not a number, such as
+    /// the logarithm of a negative value, rank last.
+    pub fn scorer(&self, segment_reader: &SegmentReader, now: i64) -> impl Fn(DocId, Score) -> Score {
+        let root = self.root.clone();
+        let signals = ranking::signal_reader(segment_reader, now);
+        move |doc, score| {
+            let values = Values { score: score as f64, signals: signals(doc) };
+            let value = root.evaluate(&values) as Score;
+            if value.is_nan() { Score::MIN } else { value.clamp(Score::MIN, Score::MAX) }
+        }
+    }
+}
+
+impl Node {
+    fn evaluate(&self, values: &Values) -> f64 {
+        match self {
+            Self::Number(number) => *number,
+            Self::Variable(variable) => match variable {
+                Variable::Score => values.score,
+                Variable::Depth => values.signals.depth,
+                Variable::Authority => values.signals.authority,
+                Variable::UrlLength => values.signals.url_length,
+                Variable::AgeDays => values.signals.age_days.unwrap_or_default(),
+            },
+            Self::Negate(operand) => -operand.evaluate(values),
+            Self::Binary(operator, left, right) => {
+                let (left, right) = (left.evaluate(values), right.evaluate(values));
+                match operator {
+                    Operator::Add => left + right,
+                    Operator::Subtract => left - right,
+                    Operator::Multiply => left * right,
+                    Operator::Divide => left / right,
+                    Operator::Power => left.powf(right),
+                }
+            }
+            Self::Call(function, arguments) => {
+                let arguments: Vec<f64> = arguments.iter().map(|argument| argument.evaluate(values)).collect();
+                function.apply(&arguments)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate(text: &str) -> f64 {
+        let signals = Signals { authority: 0.5, depth: 3.0, age_days: Some(1.5), url_length: 20.0 };
+        let values = Values { score: 2.0, signals };
+        Expression::parse(text).unwrap().root.evaluate(&values)
+    }
+
+    #[test]
+    fn operators_follow_precedence() {
+        assert_eq!(evaluate("1 + 2 * 3"), 7.0);
+        assert_eq!(evaluate("(1 + 2) * 3"), 9.0);
+        assert_eq!(evaluate("8 / 2 / 2"), 2.0);
+        assert_eq!(evaluate("1 - 2 - 3"), -4.0);
+        assert_eq!(evaluate("2 ^ 3 ^ 2"), 512.0);
+        assert_eq!(evaluate("-2 ^ 2"), -4.0);
+        assert_eq!(evaluate("2 * -3"), -6.0);
+    }
+
+    #[test]
+    fn variables_and_functions_are_evaluated() {
+        assert_eq!(evaluate("score / (1 + depth)"), 0.5);
+        assert_eq!(evaluate("max(authority, url_length) + min(1, age_days)"), 21.0);
+        assert_eq!(evaluate("pow(2, 10)"), 1024.0);
+        assert_eq!(evaluate("log(exp(1))"), 1.0);
+        assert_eq!(evaluate("sqrt(abs(-16))"), 4.0);
+    }
+
+    #[test]
+    fn wrong_arities_are_rejected() {
+        assert!(Expression::parse("min(1)").unwrap_err().contains("takes 2 argument(s)"));
+        assert!(Expression::parse("log(1, 2)").unwrap_err().contains("takes 1 argument(s)"));
+    }
+
+    #[test]
+    fn invalid_expressions_are_rejected() {
+        assert!(Expression::parse("rank * 2").unwrap_err().contains("Unknown variable rank"));
+        assert!(Expression::parse("foo(1)").unwrap_err().contains("Unknown function foo"));
+        assert!(Expression::parse("1 +").is_err());
+        assert!(Expression::parse("(1 + 2").is_err());
+        assert!(Expression::parse("1 2").unwrap_err().contains("trailing input"));
+        assert!(Expression::parse("1 % 2").unwrap_err().contains("Unexpected character"));
+    }
+
+    #[test]
+    fn nesting_is_bounded() {
+        let nested = |depth: usize| format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+        assert_eq!(evaluate(&nested(MAX_DEPTH - 1)), 1.0);
+        assert!(Expression::parse(&nested(MAX_DEPTH)).unwrap_err().contains("levels deep"));
+        assert!(Expression::parse(&"-".repeat(MAX_DEPTH * 2)).unwrap_err().contains("levels deep"));
+        assert!(Expression::parse(&nested(20_000)).unwrap_err().contains("bytes long"));
+    }
+
+    #[test]
+    fn length_is_bounded() {
+        let sum = vec!["1"; MAX_LENGTH].join("+");
+        assert!(Expression::parse(&sum).unwrap_err().contains("bytes long"));
+    }
+}
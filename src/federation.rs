@@ -0,0 +1,170 @@
+//! Federated search: searches forwarded to the peer servers, whose results are merged with the
+//! results of this server.
+
+use std::collections::HashMap;
+use std::env;
+use std::time::Duration;
+
+use tonic::transport::{Channel, Endpoint};
+
+use crate::search::searcher_client::SearcherClient;
+use crate::search::{FacetBucket, FacetResult, PeerFailure, ResponseStatus, SearchRequest, SearchResponse, SearchResult};
+use crate::search_engine::{result_window, DEFAULT_FACET_SIZE};
+use crate::sort::result_sort_value;
+
+// Time a peer has to answer unless configured otherwise.
+const DEFAULT_PEER_TIMEOUT: Duration = Duration::from_millis(1_000);
+
+/// A peer server along with the time it has to answer.
+#[derive(Clone, Debug)]
+pub struct PeerConfig {
+    pub url: String,
+    pub timeout: Duration,
+}
+
+/// Peers searches are forwarded to. Without peers, searches stay local.
+#[derive(Clone, Debug, Default)]
+pub struct FederationConfig {
+    pub peers: Vec<PeerConfig>,
+}
+
+impl FederationConfig {
+    /// Peers listed by the `SEARCH_PEERS` environment variable, separated by commas. Each peer may
+    /// be followed by `=` and its timeout in milliseconds, which is `SEARCH_PEER_TIMEOUT_MS` or
+    /// one second otherwise.
+    pub fn from_env() -> Result<Self, String> {
+        let timeout = |value: &str, name: &str| -> Result<Duration, String> {
+            value.parse().map(Duration::from_millis).map_err(|_| format!("Invalid {}: {}", name, value))
+        };
+        let default_timeout = match env::var("SEARCH_PEER_TIMEOUT_MS") {
+            Ok(value) => timeout(&value, "SEARCH_PEER_TIMEOUT_MS")?,
+            Err(_) => DEFAULT_PEER_TIMEOUT,
+        };
+        let Ok(peers) = env::var("SEARCH_PEERS") else { return Ok(Self::default()) };
+        let peers = peers.split(',').map(str::trim).filter(|peer| !peer.is_empty()).map(|peer| match peer.rsplit_once('=') {
+            Some((url, peer_timeout)) => Ok(PeerConfig { url: url.to_string(), timeout: timeout(peer_timeout, "SEARCH_PEERS timeout")? }),
+            None => Ok(PeerConfig { url: peer.to_string(), timeout: default_timeout }),
+        }).collect::<Result<_, String>>()?;
+        Ok(Self { peers })
+    }
+}
+
+struct Peer {
+    url: String,
+    timeout: Duration,
+    client: SearcherClient<Channel>,
+}
+
+pub struct Federation {
+    peers: Vec<Peer>,
+}
+
+impl Federation {
+    /// Connects lazily to the peers, so that peers down at startup only fail the searches.
+    pub fn new(config: FederationConfig) -> Result<Self, String> {
+        let peers = config.peers.into_iter().map(|peer| {
+            let endpoint = Endpoint::from_shared(peer.url.clone()).map_err(|e| format!("Invalid peer {}: {}", peer.url, e))?;
+            Ok(Peer { client: SearcherClient::new(endpoint.connect_lazy()), url: peer.url, timeout: peer.timeout })
+        }).collect::<Result<_, String>>()?;
+        Ok(Self { peers })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.peers.is_empty()
+    }
+
+    /// Forwards the search to all peers while running it locally, then merges the results of all
+    /// servers. Peers that fail or time out are reported in the response, which is then partial.
+    pub async fn search(&self, request: &SearchRequest, local: impl FnOnce(&SearchRequest) -> Result<SearchResponse, String>) -> Result<SearchResponse, String> {
+        // Each server returns all the results up to the requested page, which is cut from the
+        // merged results.
+        let (offset, limit) = result_window(request.offset, request.limit)?;
+        let forwarded = SearchRequest {
+            offset: 0,
+            limit: Some((offset + limit) as u32),
+            local: true,
+            ..request.clone()
+        };
+        let searches: Vec<_> = self.peers.iter().map(|peer| {
+            let (mut client, timeout, request) = (peer.client.clone(), peer.timeout, forwarded.clone());
+            tokio::spawn(async move {
+                match tokio::time::timeout(timeout, client.search(request)).await {
+                    Ok(Ok(response)) => Ok(response.into_inner()),
+                    Ok(Err(status)) => Err(status.message().to_string()),
+                    Err(_) => Err(format!("No response within {} ms", timeout.as_millis())),
+                }
+            })
+        }).collect();
+        let mut responses = vec![(String::new(), local(&forwarded)?)];
+        let mut failed_peers = Vec::new();
+        for (peer, search) in self.peers.iter().zip(searches) {
+            match search.await.map_err(|e| e.to_string()).and_then(|response| response) {
+                Ok(response) => responses.push((peer.url.clone(), response)),
+                Err(message) => failed_peers.push(PeerFailure { peer: peer.url.clone(), message }),
+            }
+        }
+        let mut response = merge(request, responses, offset, limit);
+        if !failed_peers.is_empty() {
+            response.status = ResponseStatus::Partial.into();
+            response.message = Some(format!("{} of {} peers failed", failed_peers.len(), self.peers.len()));
+            response.failed_peers = failed_peers;
+        }
+        Ok(response)
+    }
+}
+
+/// Merges the responses of several servers, by peer. Scores are divided by the best score of
+/// their server, as servers score with the statistics of their own index. Pages returned by
+/// several servers are kept once, with their best score, although total hits count them all.
+fn merge(request: &SearchRequest, responses: Vec<(String, SearchResponse)>, offset: usize, limit: usize) -> SearchResponse {
+    let mut results: HashMap<String, SearchResult> = HashMap::new();
+    let mut facets: Vec<(i32, HashMap<String, u64>)> = request.facets.iter().map(|kind| (*kind, HashMap::new())).collect();
+    let mut total_hits = 0;
+    let mut suggestion = None;
+    for (peer, response) in responses {
+        let best = response.results.iter().map(|result| result.score).fold(0.0, f32::max);
+        for mut result in response.results {
+            if best > 0.0 {
+                result.score /= best;
+            }
+            result.peer = peer.clone();
+            match results.get(&result.relevant_url) {
+                Some(previous) if previous.score >= result.score => {}
+                _ => { results.insert(result.relevant_url.clone(), result); }
+            }
+        }
+        // Buckets beyond the facet size of a server are not counted.
+        for facet in response.facets {
+            if let Some((_, counts)) = facets.iter_mut().find(|(kind, _)| *kind == facet.kind) {
+                for bucket in facet.buckets {
+                    *counts.entry(bucket.value).or_default() += bucket.count;
+                }
+            }
+        }
+        total_hits += response.total_hits;
+        suggestion = suggestion.or(response.suggestion);
+    }
+    let (sort_by, then_by) = (request.sort_by(), request.then_by());
+    let mut results: Vec<SearchResult> = results.into_values().collect();
+    results.sort_by(|a, b| {
+        let a_key = (result_sort_value(sort_by, a), result_sort_value(then_by, a), a.score);
+        let b_key = (result_sort_value(sort_by, b), result_sort_value(then_by, b), b.score);
+        b_key.partial_cmp(&a_key).unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.relevant_url.cmp(&b.relevant_url))
+    });
+    let facet_size = request.facet_size.unwrap_or(DEFAULT_FACET_SIZE) as usize;
+    SearchResponse {
+        status: ResponseStatus::Ok.into(),
+        message: None,
+        results: results.into_iter().skip(offset).take(limit).collect(),
+        facets: facets.into_iter().map(|(kind, counts)| {
+            let mut buckets: Vec<FacetBucket> = counts.into_iter().map(|(value, count)| FacetBucket { value, count }).collect();
+            buckets.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+            buckets.truncate(facet_size);
+            FacetResult { kind, buckets }
+        }).collect(),
+        total_hits,
+        suggestion,
+        failed_peers: Vec::new(),
+    }
+}
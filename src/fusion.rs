@@ -0,0 +1,66 @@
+//! Fusion of the ranked candidates of the keyword and semantic retrievers of hybrid searches.
+
+use std::collections::HashMap;
+
+use tantivy::Score;
+
+use crate::search::{Fusion, FusionMethod, Retriever};
+
+// Rank constant of reciprocal rank fusion, damping the weight of the first ranks.
+const DEFAULT_RRF_K: f64 = 60.0;
+const DEFAULT_KEYWORD_WEIGHT: f64 = 0.5;
+const DEFAULT_SEMANTIC_WEIGHT: f64 = 0.5;
+
+/// How the candidates of the retrievers are merged.
+#[derive(Clone, Debug)]
+pub struct FusionConfig {
+    method: FusionMethod,
+    rrf_k: f64,
+    keyword_weight: f64,
+    semantic_weight: f64,
+}
+
+impl FusionConfig {
+    /// Defaults overridden by the parameters set in the request.
+    pub fn from_request(fusion: Option<&Fusion>) -> Result<Self, String> {
+        let fusion = fusion.cloned().unwrap_or_default();
+        let config = Self {
+            method: fusion.method(),
+            rrf_k: fusion.rrf_k.unwrap_or(DEFAULT_RRF_K),
+            keyword_weight: fusion.keyword_weight.unwrap_or(DEFAULT_KEYWORD_WEIGHT),
+            semantic_weight: fusion.semantic_weight.unwrap_or(DEFAULT_SEMANTIC_WEIGHT),
+        };
+        if config.rrf_k <= 0.0 {
+            return Err("Reciprocal rank fusion constant must be positive".to_string());
+        }
+        if config.keyword_weight < 0.0 || config.semantic_weight < 0.0 {
+            return Err("Fusion weights must not be negative".to_string());
+        }
+        Ok(config)
+    }
+
+    /// Merges the candidates of the keyword and semantic retrievers, each ranked best first, into
+    /// fused scores along with the retrievers that found each candidate.
+    pub fn fuse(&self, keyword: &[(String, Score)], semantic: &[(String, Score)]) -> HashMap<String, (Score, Vec<Retriever>)> {
+        // Keyword scores are unbounded, so they are scaled to the best one to blend with
+        // similarities.
+        let keyword_max = keyword.iter().map(|(_, score)| *score).fold(0.0, Score::max);
+        let mut fused: HashMap<String, (Score, Vec<Retriever>)> = HashMap::new();
+        let retrievers = [(Retriever::Keyword, keyword, self.keyword_weight), (Retriever::Semantic, semantic, self.semantic_weight)];
+        for (retriever, candidates, weight) in retrievers {
+            for (rank, (url, score)) in candidates.iter().enumerate() {
+                let contribution = match self.method {
+                    FusionMethod::ReciprocalRank => 1.0 / (self.rrf_k + rank as f64 + 1.0),
+                    FusionMethod::WeightedScore => {
+                        let score = if retriever == Retriever::Keyword && keyword_max > 0.0 { score / keyword_max } else { *score };
+                        weight * score as f64
+                    }
+                };
+                let entry = fused.entry(url.clone()).or_insert((0.0, Vec::new()));
+                entry.0 += contribution as Score;
+                entry.1.push(retriever);
+            }
+        }
+        fused
+    }
+}
@@ -0,0 +1,279 @@
+//! Hierarchical navigable small world graph: an approximate nearest neighbour index over unit
+//! vectors, compared by their dot product.
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+// Neighbours kept per node on the upper layers, twice as many on the bottom layer.
+const MAX_NEIGHBOURS: usize = 16;
+// Candidates examined when linking a new node.
+const EF_CONSTRUCTION: usize = 100;
+// Minimum number of candidates examined when searching.
+const EF_SEARCH: usize = 50;
+// Seed of the generator drawing the layers of new nodes, fixed so that indexes are reproducible.
+const SEED: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// A node of the graph along with its distance to a query, ordered by distance.
+#[derive(Clone, Copy, PartialEq)]
+struct Candidate {
+    distance: f32,
+    node: usize,
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance.total_cmp(&other.distance).then(self.node.cmp(&other.node))
+    }
+}
+
+struct Node {
+    key: String,
+    vector: Vec<f32>,
+    // Neighbours on each layer the node is on, from the bottom one.
+    neighbours: Vec<Vec<usize>>,
+    // Replaced nodes stay in the graph to keep it connected until it is rebuilt, but are never
+    // returned.
+    deleted: bool,
+}
+
+pub struct Hnsw {
+    nodes: Vec<Node>,
+    by_key: HashMap<String, usize>,
+    entry_point: Option<usize>,
+    random: u64,
+    // Number of deleted nodes.
+    deleted: usize,
+}
+
+impl Default for Hnsw {
+    fn default() -> Self {
+        Self { nodes: Vec::new(), by_key: HashMap::new(), entry_point: None, random: SEED, deleted: 0 }
+    }
+}
+
+fn distance(a: &[f32], b: &[f32]) -> f32 {
+    1.0 - a.iter().zip(b).map(|(a, b)| a * b).sum::<f32>()
+}
+
+impl Hnsw {
+    /// Draws the top layer of a new node, each layer being `MAX_NEIGHBOURS` times less likely
+    /// than the one below.
+    fn random_layer(&mut self) -> usize {
+        // xorshift64*
+        self.random ^= self.random >> 12;
+        self.random ^= self.random << 25;
+        self.random ^= self.random >> 27;
+        let uniform = (self.random.wrapping_mul(0x2545_f491_4f6c_dd1d) >> 11) as f64 / (1u64 << 53) as f64;
+        (-(1.0 - uniform).ln() / (MAX_NEIGHBOURS as f64).ln()) as usize
+    }
+
+    fn top_layer(&self) -> usize {
+        self.entry_point.map_or(0, |entry_point| self.nodes[entry_point].neighbours.len() - 1)
+    }
+
+    fn max_neighbours(layer: usize) -> usize {
+        if layer == 0 { 2 * MAX_NEIGHBOURS } else { MAX_NEIGHBOURS }
+    }
+
+    /// Greedy best-first search of the layer from the entry points, keeping the `ef` live nodes
+    /// closest to the query, closest first. Deleted nodes are walked through but not kept.
+    fn search_layer(&self, query: &[f32], entry_points: &[usize], ef: usize, layer: usize) -> Vec<Candidate> {
+        let mut visited: HashSet<usize> = entry_points.iter().copied().collect();
+        let mut candidates: BinaryHeap<Reverse<Candidate>> = BinaryHeap::new();
+        let mut nearest: BinaryHeap<Candidate> = BinaryHeap::new();
+        for &node in entry_points {
+            let candidate = Candidate { distance: distance(query, &self.nodes[node].vector), node };
+            candidates.push(Reverse(candidate));
+            if !self.nodes[node].deleted {
+                nearest.push(candidate);
+            }
+        }
+        while let Some(Reverse(candidate)) = candidates.pop() {
+            if nearest.len() >= ef && candidate.distance > nearest.peek().unwrap().distance {
+                break;
+            }
+            for &neighbour in &self.nodes[candidate.node].neighbours[layer] {
+                if !visited.insert(neighbour) {
+                    continue;
+                }
+                let neighbour = Candidate { distance: distance(query, &self.nodes[neighbour].vector), node: neighbour };
+                if nearest.len() < ef || neighbour.distance < nearest.peek().unwrap().distance {
+                    candidates.push(Reverse(neighbour));
+                    if !self.nodes[neighbour.node].deleted {
+                        nearest.push(neighbour);
+                        if nearest.len() > ef {
+                            nearest.pop();
+                        }
+                    }
+                }
+            }
+        }
+        nearest.into_sorted_vec()
+    }
+
+    /// Descends from the entry point to `layer`, following the closest node on each layer above.
+    fn descend(&self, query: &[f32], layer: usize) -> Vec<usize> {
+        let Some(mut entry_point) = self.entry_point else { return Vec::new() };
+        for upper in (layer + 1..=self.top_layer()).rev() {
+            if let Some(nearest) = self.search_layer(query, &[entry_point], 1, upper).first() {
+                entry_point = nearest.node;
+            }
+        }
+        vec![entry_point]
+    }
+
+    /// Adds the vector of a key, replacing its previous vector.
+    pub fn insert(&mut self, key: &str, vector: Vec<f32>) {
+        self.remove(key);
+        let node = self.nodes.len();
+        let layer = self.random_layer();
+        self.nodes.push(Node { key: key.to_string(), vector, neighbours: vec![Vec::new(); layer + 1], deleted: false });
+        self.by_key.insert(key.to_string(), node);
+        let top_layer = self.top_layer();
+        if self.entry_point.is_none() {
+            self.entry_point = Some(node);
+            return;
+        }
+        let query = self.nodes[node].vector.clone();
+        let mut entry_points = self.descend(&query, layer.min(top_layer));
+        for current in (0..=layer.min(top_layer)).rev() {
+            let nearest = self.search_layer(&query, &entry_points, EF_CONSTRUCTION, current);
+            let neighbours: Vec<usize> = nearest.iter().take(MAX_NEIGHBOURS).map(|candidate| candidate.node).collect();
+            for &neighbour in &neighbours {
+                self.nodes[neighbour].neighbours[current].push(node);
+                self.prune(neighbour, current);
+            }
+            self.nodes[node].neighbours[current] = neighbours;
+            entry_points = nearest.iter().map(|candidate| candidate.node).collect();
+        }
+        if layer > top_layer {
+            self.entry_point = Some(node);
+        }
+    }
+
+    /// Keeps the closest neighbours of the node on the layer.
+    fn prune(&mut self, node: usize, layer: usize) {
+        if self.nodes[node].neighbours[layer].len() <= Self::max_neighbours(layer) {
+            return;
+        }
+        let vector = &self.nodes[node].vector;
+        let mut neighbours: Vec<Candidate> = self.nodes[node].neighbours[layer].iter()
+            .map(|&neighbour| Candidate { distance: distance(vector, &self.nodes[neighbour].vector), node: neighbour })
+            .collect();
+        neighbours.sort();
+        neighbours.truncate(Self::max_neighbours(layer));
+        self.nodes[node].neighbours[layer] = neighbours.into_iter().map(|candidate| candidate.node).collect();
+    }
+
+    /// Removes the vector of a key. The graph is rebuilt once deleted nodes outnumber live ones,
+    /// so that replaced vectors neither slow searches down nor hold memory.
+    pub fn remove(&mut self, key: &str) {
+        let Some(node) = self.by_key.remove(key) else { return };
+        self.nodes[node].deleted = true;
+        self.deleted += 1;
+        if self.deleted > self.by_key.len() {
+            self.rebuild();
+        }
+    }
+
+    fn rebuild(&mut self) {
+        let nodes = std::mem::take(&mut self.nodes);
+        *self = Self { random: self.random, ..Self::default() };
+        for node in nodes.into_iter().filter(|node| !node.deleted) {
+            self.insert(&node.key, node.vector);
+        }
+    }
+
+    /// Finds the keys of the `k` vectors closest to the query, with their dot product with it,
+    /// highest first.
+    pub fn search(&self, query: &[f32], k: usize) -> Vec<(String, f32)> {
+        let entry_points = self.descend(query, 0);
+        if entry_points.is_empty() {
+            return Vec::new();
+        }
+        self.search_layer(query, &entry_points, EF_SEARCH.max(k), 0).into_iter()
+            .filter(|candidate| !self.nodes[candidate.node].deleted)
+            .take(k)
+            .map(|candidate| (self.nodes[candidate.node].key.clone(), 1.0 - candidate.distance))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit(seed: u64) -> Vec<f32> {
+        let mut random = seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1;
+        let vector: Vec<f32> = (0..16).map(|_| {
+            random ^= random << 13;
+            random ^= random >> 7;
+            random ^= random << 17;
+            (random % 1_000) as f32 / 500.0 - 1.0
+        }).collect();
+        let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+        vector.into_iter().map(|x| x / norm).collect()
+    }
+
+    #[test]
+    fn finds_inserted_vectors() {
+        let mut hnsw = Hnsw::default();
+        for i in 0..200 {
+            hnsw.insert(&i.to_string(), unit(i));
+        }
+        for i in [0, 57, 199] {
+            let results = hnsw.search(&unit(i), 5);
+            assert_eq!(results.len(), 5);
+            assert_eq!(results[0].0, i.to_string());
+            assert!((results[0].1 - 1.0).abs() < 1e-5);
+            assert!(results.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+        }
+    }
+
+    #[test]
+    fn replaced_vectors_are_not_returned() {
+        let mut hnsw = Hnsw::default();
+        hnsw.insert("page", unit(1));
+        hnsw.insert("other", unit(2));
+        hnsw.insert("page", unit(3));
+        let results = hnsw.search(&unit(1), 10);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results.iter().filter(|(key, _)| key == "page").count(), 1);
+        let (_, score) = results.iter().find(|(key, _)| key == "page").unwrap();
+        assert!((score - (1.0 - distance(&unit(1), &unit(3)))).abs() < 1e-5);
+    }
+
+    #[test]
+    fn searches_find_live_vectors_after_many_replacements() {
+        let mut hnsw = Hnsw::default();
+        for round in 0..20 {
+            for i in 0..50 {
+                hnsw.insert(&i.to_string(), unit(round * 50 + i));
+            }
+        }
+        assert!(hnsw.nodes.len() <= 2 * 50 + 1);
+        let results = hnsw.search(&unit(19 * 50 + 7), 10);
+        assert_eq!(results.len(), 10);
+        assert_eq!(results[0].0, "7");
+    }
+
+    #[test]
+    fn removed_keys_are_not_returned() {
+        let mut hnsw = Hnsw::default();
+        for i in 0..10 {
+            hnsw.insert(&i.to_string(), unit(i));
+        }
+        hnsw.remove("3");
+        let results = hnsw.search(&unit(3), 10);
+        assert_eq!(results.len(), 9);
+        assert!(results.iter().all(|(key, _)| key != "3"));
+    }
+}
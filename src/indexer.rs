@@ -1,38 +1,74 @@
-use crate::crawly::CrawlerBuilder;
-use crate::search::SearchResult;
-use crate::search_engine::{Reader, SearchEngine};
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
+use crate::cache::CacheConfig;
+use crate::collection::{self, Collection, CollectionConfig, DEFAULT_COLLECTION};
+use crate::crawly::CrawlObserver;
+use crate::ranking::RankingConfig;
+use crate::search::CollectionOptions;
 
 pub trait Indexer {
-    async fn visit(&self, url: &str, max_depth: u32) -> anyhow::Result<()>;
+    async fn visit(&self, url: &str, max_depth: u32, observer: Arc<dyn CrawlObserver + Send + Sync>) -> anyhow::Result<()>;
 }
 
+/// The collections of the server, by name.
 pub struct IndexerService {
-    search_engine: SearchEngine,
+    collections: RwLock<BTreeMap<String, Arc<Collection>>>,
+    // Settings of the default collection, and of the others unless their options say otherwise.
+    defaults: CollectionConfig,
+    ranking: RankingConfig,
+    cache: CacheConfig,
 }
 
 impl Default for IndexerService {
     fn default() -> Self {
+        Self::new(RankingConfig::default(), CacheConfig::default(), CollectionConfig::default())
+    }
+}
+
+impl IndexerService {
+    pub fn new(ranking: RankingConfig, cache: CacheConfig, defaults: CollectionConfig) -> Self {
+        let default = Collection::new(DEFAULT_COLLECTION.to_string(), defaults.clone(), ranking.clone(), cache.clone());
         Self {
-            search_engine: SearchEngine::default(),
+            collections: RwLock::new(BTreeMap::from([(DEFAULT_COLLECTION.to_string(), Arc::new(default))])),
+            defaults,
+            ranking,
+            cache,
         }
     }
-}
 
-impl Indexer for IndexerService {
-    async fn visit(&self, origin_url: &str, max_depth: u32) -> anyhow::Result<()> {
-        let crawler = CrawlerBuilder::new()
-            .with_max_depth(max_depth as usize)
-            .with_max_pages(3)
-            .with_max_concurrent_requests(2)
-            .with_robots(true)
-            .build()?;
-        crawler.start(origin_url.to_string(), &self.search_engine).await?;
-        Ok(())
+    /// The collection with the given name, the default collection if the name is empty.
+    pub fn collection(&self, name: &str) -> Result<Arc<Collection>, String> {
+        let name = if name.is_empty() { DEFAULT_COLLECTION } else { name };
+        self.collections.read().unwrap().get(name).cloned().ok_or_else(|| format!("No collection named {}", name))
     }
-}
 
-impl Reader for IndexerService {
-    fn read(&self, query: &str) -> Result<Vec<SearchResult>, String> {
-        self.search_engine.read(query)
+    pub fn create_collection(&self, name: &str, options: &CollectionOptions) -> Result<Arc<Collection>, String> {
+        collection::check_name(name)?;
+        let config = self.defaults.with_options(options)?;
+        if self.collections.read().unwrap().contains_key(name) {
+            return Err(format!("Collection {} already exists", name));
+        }
+        // The index is created before taking the lock, which searches of other collections wait
+        // for.
+        let created = Arc::new(Collection::new(name.to_string(), config, self.ranking.clone(), self.cache.clone()));
+        let mut collections = self.collections.write().unwrap();
+        if collections.contains_key(name) {
+            return Err(format!("Collection {} already exists", name));
+        }
+        collections.insert(name.to_string(), created.clone());
+        Ok(created)
+    }
+
+    pub fn list_collections(&self) -> Vec<Arc<Collection>> {
+        self.collections.read().unwrap().values().cloned().collect()
+    }
+
+    /// Removes the collection. Its index is deleted once the requests using it are done.
+    pub fn drop_collection(&self, name: &str) -> Result<Arc<Collection>, String> {
+        if name == DEFAULT_COLLECTION {
+            return Err("The default collection cannot be dropped".to_string());
+        }
+        self.collections.write().unwrap().remove(name).ok_or_else(|| format!("No collection named {}", name))
     }
 }
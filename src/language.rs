@@ -0,0 +1,133 @@
+//! Language detection of crawled pages and the language-specific analyzers used to index them.
+
+use tantivy::Index;
+use tantivy::tokenizer::{Language, LowerCaser, RemoveLongFilter, SimpleTokenizer, Stemmer, TextAnalyzer};
+use whatlang::Lang;
+
+use crate::cjk::CjkTokenizer;
+
+/// Languages with a stemming analyzer, by ISO 639-1 code.
+pub const STEMMED_LANGUAGES: [(&str, Language); 18] = [
+    ("ar", Language::Arabic),
+    ("da", Language::Danish),
+    ("de", Language::German),
+    ("el", Language::Greek),
+    ("en", Language::English),
+    ("es", Language::Spanish),
+    ("fi", Language::Finnish),
+    ("fr", Language::French),
+    ("hu", Language::Hungarian),
+    ("it", Language::Italian),
+    ("nl", Language::Dutch),
+    ("no", Language::Norwegian),
+    ("pt", Language::Portuguese),
+    ("ro", Language::Romanian),
+    ("ru", Language::Russian),
+    ("sv", Language::Swedish),
+    ("ta", Language::Tamil),
+    ("tr", Language::Turkish),
+];
+
+/// Languages written without spaces between words, by ISO 639-1 code. They share an analyzer of
+/// characters and bigrams.
+pub const CJK_LANGUAGES: [&str; 3] = ["zh", "ja", "ko"];
+
+const CJK_FIELD: &str = "body_cjk";
+const CJK_ANALYZER: &str = "cjk";
+
+fn stemmed_field_name(code: &str) -> String {
+    format!("body_{}", code)
+}
+
+fn stemmer_name(code: &str) -> String {
+    format!("stem_{}", code)
+}
+
+/// Name of the field holding the body analyzed for the given language, if the language has a
+/// dedicated analyzer.
+pub fn body_field_name(code: &str) -> Option<String> {
+    if STEMMED_LANGUAGES.iter().any(|(stemmed, _)| *stemmed == code) {
+        Some(stemmed_field_name(code))
+    } else if CJK_LANGUAGES.contains(&code) {
+        Some(CJK_FIELD.to_string())
+    } else {
+        None
+    }
+}
+
+/// Names of all language-specific body fields, along with the analyzer each one is indexed with.
+pub fn body_fields() -> Vec<(String, String)> {
+    let mut fields: Vec<(String, String)> = STEMMED_LANGUAGES.iter()
+        .map(|(code, _)| (stemmed_field_name(code), stemmer_name(code)))
+        .collect();
+    fields.push((CJK_FIELD.to_string(), CJK_ANALYZER.to_string()));
+    fields
+}
+
+/// Registers the analyzers of all supported languages on the index.
+pub fn register_analyzers(index: &Index) {
+    for (code, language) in STEMMED_LANGUAGES {
+        let analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(RemoveLongFilter::limit(40))
+            .filter(LowerCaser)
+            .filter(Stemmer::new(language))
+            .build();
+        index.tokenizers().register(&stemmer_name(code), analyzer);
+    }
+    let analyzer = TextAnalyzer::builder(CjkTokenizer)
+        .filter(RemoveLongFilter::limit(40))
+        .filter(LowerCaser)
+        .build();
+    index.tokenizers().register(CJK_ANALYZER, analyzer);
+}
+
+/// Detects the language of a text and returns its ISO 639-1 code, or the ISO 639-3 code for
+/// languages without a two-letter one. `hint` is the language declared by the page, such as the
+/// `lang` attribute, and is used when detection is not reliable. Returns an empty string when the
+/// language is unknown.
+pub fn detect(text: &str, hint: Option<&str>) -> String {
+    match whatlang::detect(text) {
+        Some(info) if info.is_reliable() => code(info.lang()).to_string(),
+        detected => hint
+            .and_then(|hint| hint.split(['-', '_']).next())
+            .map(|hint| hint.trim().to_lowercase())
+            .filter(|hint| !hint.is_empty())
+            .or_else(|| detected.map(|info| code(info.lang()).to_string()))
+            .unwrap_or_default()
+    }
+}
+
+fn code(lang: Lang) -> &'static str {
+    match lang {
+        Lang::Ara => "ar",
+        Lang::Ces => "cs",
+        Lang::Cmn => "zh",
+        Lang::Dan => "da",
+        Lang::Deu => "de",
+        Lang::Ell => "el",
+        Lang::Eng => "en",
+        Lang::Fin => "fi",
+        Lang::Fra => "fr",
+        Lang::Heb => "he",
+        Lang::Hin => "hi",
+        Lang::Hun => "hu",
+        Lang::Ind => "id",
+        Lang::Ita => "it",
+        Lang::Jpn => "ja",
+        Lang::Kor => "ko",
+        Lang::Nld => "nl",
+        Lang::Nob => "no",
+        Lang::Pol => "pl",
+        Lang::Por => "pt",
+        Lang::Ron => "ro",
+        Lang::Rus => "ru",
+        Lang::Spa => "es",
+        Lang::Swe => "sv",
+        Lang::Tam => "ta",
+        Lang::Tha => "th",
+        Lang::Tur => "tr",
+        Lang::Ukr => "uk",
+        Lang::Vie => "vi",
+        lang => lang.code(),
+    }
+}
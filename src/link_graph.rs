@@ -0,0 +1,77 @@
+//! Link graph between crawled pages and PageRank authority scores computed over it.
+
+use std::collections::HashMap;
+
+const DAMPING: f64 = 0.85;
+const MAX_ITERATIONS: usize = 50;
+const TOLERANCE: f64 = 1e-6;
+
+/// Directed source→target graph of the links found on crawled pages, labelled with anchor text.
+#[derive(Default)]
+pub struct LinkGraph {
+    outlinks: HashMap<String, Vec<(String, String)>>,
+}
+
+impl LinkGraph {
+    /// Records the outlinks of a page as (target, anchor text) pairs, replacing any previously
+    /// recorded ones.
+    pub fn add(&mut self, source: &str, targets: Vec<(String, String)>) {
+        self.outlinks.insert(source.to_string(), targets);
+    }
+
+    /// Collects, for every link target, the non-empty anchor texts of the links pointing to it
+    /// from other pages.
+    pub fn anchor_texts(&self) -> HashMap<String, Vec<String>> {
+        let mut anchor_texts: HashMap<String, Vec<String>> = HashMap::new();
+        for (source, targets) in &self.outlinks {
+            for (target, anchor_text) in targets {
+                if target != source && !anchor_text.is_empty() {
+                    anchor_texts.entry(target.clone()).or_default().push(anchor_text.clone());
+                }
+            }
+        }
+        anchor_texts
+    }
+
+    /// Computes PageRank over the pages recorded as link sources. Links to pages that were never
+    /// recorded are ignored and the rank of pages without outlinks is spread evenly.
+    ///
+    /// Scores are scaled so that their mean is 1, which keeps them comparable across graph sizes.
+    pub fn page_rank(&self) -> HashMap<String, f64> {
+        let nodes: Vec<&String> = self.outlinks.keys().collect();
+        let count = nodes.len();
+        if count == 0 {
+            return HashMap::new();
+        }
+        let ids: HashMap<&String, usize> = nodes.iter().enumerate().map(|(id, node)| (*node, id)).collect();
+        let edges: Vec<Vec<usize>> = nodes.iter().map(|node| {
+            let mut targets: Vec<usize> = self.outlinks[*node].iter()
+                .filter_map(|(target, _)| ids.get(target).copied())
+                .collect();
+            targets.sort_unstable();
+            targets.dedup();
+            targets
+        }).collect();
+        let n = count as f64;
+        let mut ranks = vec![1.0 / n; count];
+        for _ in 0..MAX_ITERATIONS {
+            let dangling: f64 = edges.iter().zip(&ranks)
+                .filter(|(targets, _)| targets.is_empty())
+                .map(|(_, rank)| rank)
+                .sum();
+            let mut next = vec![(1.0 - DAMPING) / n + DAMPING * dangling / n; count];
+            for (source, targets) in edges.iter().enumerate() {
+                let share = DAMPING * ranks[source] / targets.len() as f64;
+                for target in targets {
+                    next[*target] += share;
+                }
+            }
+            let delta: f64 = next.iter().zip(&ranks).map(|(a, b)| (a - b).abs()).sum();
+            ranks = next;
+            if delta < TOLERANCE {
+                break;
+            }
+        }
+        nodes.into_iter().zip(ranks).map(|(node, rank)| (node.clone(), rank * n)).collect()
+    }
+}
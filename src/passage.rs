@@ -0,0 +1,161 @@
+//! Passages: overlapping windows of the visible text of pages, indexed apart from the pages so
+//! that a long page ranks by its best passage rather than by its whole body.
+
+use std::collections::HashMap;
+use std::env;
+use std::path::Path;
+use std::sync::Mutex;
+
+use tantivy::collector::TopDocs;
+use tantivy::query::QueryParser;
+use tantivy::schema::{Field, Schema, STORED, STRING, TEXT};
+use tantivy::{doc, Index, IndexReader, IndexWriter, ReloadPolicy, Score, Term};
+
+// Passages examined per search, which bounds the number of pages a passage search returns.
+const MAX_CANDIDATES: usize = 1_000;
+
+/// Size of the passages in words. A size of zero disables passages.
+#[derive(Clone, Debug)]
+pub struct PassageConfig {
+    pub words: usize,
+    // Words shared by consecutive passages, so that answers spanning a boundary stay whole.
+    pub overlap: usize,
+}
+
+impl Default for PassageConfig {
+    fn default() -> Self {
+        Self { words: 100, overlap: 25 }
+    }
+}
+
+impl PassageConfig {
+    /// Default sizes, overridden by the `SEARCH_PASSAGE_WORDS` and `SEARCH_PASSAGE_OVERLAP`
+    /// environment variables.
+    pub fn from_env() -> Result<Self, String> {
+        let size = |name: &str, default: usize| -> Result<usize, String> {
+            match env::var(name) {
+                Ok(value) => value.parse().map_err(|_| format!("Invalid {}: {}", name, value)),
+                Err(_) => Ok(default),
+            }
+        };
+        let default = Self::default();
+        let config = Self {
+            words: size("SEARCH_PASSAGE_WORDS", default.words)?,
+            overlap: size("SEARCH_PASSAGE_OVERLAP", default.overlap)?,
+        };
+        if config.words > 0 && config.overlap >= config.words {
+            return Err("Passage overlap must be smaller than the passage size".to_string());
+        }
+        Ok(config)
+    }
+
+    /// Splits the text into passages, each with the offset in characters of its first word.
+    /// Texts that fit in one passage make a single passage.
+    pub fn split(&self, text: &str) -> Vec<(usize, String)> {
+        // Byte and character offsets of the start and byte offset of the end of each word.
+        let mut words: Vec<(usize, usize, usize)> = Vec::new();
+        let mut chars = 0;
+        let mut previous_end = 0;
+        for word in text.split_whitespace() {
+            let start = word.as_ptr() as usize - text.as_ptr() as usize;
+            chars += text[previous_end..start].chars().count();
+            words.push((start, chars, start + word.len()));
+            chars += word.chars().count();
+            previous_end = start + word.len();
+        }
+        if self.words == 0 || words.is_empty() {
+            return Vec::new();
+        }
+        let mut passages = Vec::new();
+        let mut first = 0;
+        loop {
+            let last = (first + self.words).min(words.len()) - 1;
+            let (start, offset, _) = words[first];
+            passages.push((offset, text[start..words[last].2].to_string()));
+            if last == words.len() - 1 {
+                return passages;
+            }
+            first += self.words - self.overlap;
+        }
+    }
+}
+
+/// The best passage of a page matching a query.
+pub struct BestPassage {
+    pub score: Score,
+    pub offset: usize,
+    pub text: String,
+}
+
+/// Index of the passages of all pages, by page URL.
+pub struct PassageIndex {
+    config: PassageConfig,
+    index: Index,
+    index_writer: Mutex<IndexWriter>,
+    reader: IndexReader,
+    page_url: Field,
+    offset: Field,
+    text: Field,
+}
+
+impl PassageIndex {
+    pub fn create_in_dir(path: &Path, config: PassageConfig) -> Self {
+        let mut schema_builder = Schema::builder();
+        let page_url = schema_builder.add_text_field("page_url", STRING | STORED);
+        let offset = schema_builder.add_u64_field("offset", STORED);
+        let text = schema_builder.add_text_field("text", TEXT | STORED);
+        std::fs::create_dir_all(path).expect("Unable to create passage dir");
+        let index = Index::create_in_dir(path, schema_builder.build()).expect("Unable to create passage index");
+        let index_writer = index.writer(15_000_000).expect("Unable to create passage writer");
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::OnCommit)
+            .try_into().expect("Unable to create passage reader");
+        Self { config, index, index_writer: Mutex::new(index_writer), reader, page_url, offset, text }
+    }
+
+    /// Replaces the passages of the page with the passages of its body, once committed.
+    pub fn add(&self, url: &str, body: &str) -> Result<(), String> {
+        let guard = self.index_writer.lock().unwrap();
+        guard.delete_term(Term::from_field_text(self.page_url, url));
+        for (offset, text) in self.config.split(body) {
+            guard.add_document(doc!(
+                self.page_url => url,
+                self.offset => offset as u64,
+                self.text => text
+            )).map_err(|e| format!("Failed to index passages of {}. Error: {}", url, e))?;
+        }
+        Ok(())
+    }
+
+    pub fn commit(&self) -> Result<(), String> {
+        self.index_writer.lock().unwrap().commit().map_err(|e| format!("Failed to commit passages. Error: {}", e))?;
+        Ok(())
+    }
+
+    /// Finds the passages matching the query and keeps the best one of each page, by page URL.
+    pub fn search(&self, query: &str, conjunction: bool) -> Result<HashMap<String, BestPassage>, String> {
+        let mut query_parser = QueryParser::for_index(&self.index, vec![self.text]);
+        if conjunction {
+            query_parser.set_conjunction_by_default();
+        }
+        let query = query_parser.parse_query(query).map_err(|e| e.to_string())?;
+        let searcher = self.reader.searcher();
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(MAX_CANDIDATES)).map_err(|e| e.to_string())?;
+        let mut best: HashMap<String, BestPassage> = HashMap::new();
+        // Passages come best first, so the first one of each page is its best.
+        for (score, doc_address) in top_docs {
+            let retrieved = searcher.doc(doc_address).map_err(|e| e.to_string())?;
+            let page_url = retrieved.get_first(self.page_url).and_then(|value| value.as_text()).unwrap_or_default();
+            if best.contains_key(page_url) {
+                continue;
+            }
+            best.insert(page_url.to_string(), BestPassage {
+                score,
+                offset: retrieved.get_first(self.offset).and_then(|value| value.as_u64()).unwrap_or_default() as usize,
+                text: retrieved.get_first(self.text).and_then(|value| value.as_text()).unwrap_or_default().to_string(),
+            });
+        }
+        Ok(best)
+    }
+}
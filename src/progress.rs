@@ -0,0 +1,65 @@
+use futures::channel::mpsc::UnboundedSender;
+use reqwest::Url;
+use tonic::Status;
+
+use crate::crawly::{CrawlObserver, SkipReason};
+use crate::search::{CrawlEvent, CrawlEventKind};
+
+/// Forwards crawl events to a gRPC response stream.
+pub struct StreamingObserver {
+    sender: UnboundedSender<Result<CrawlEvent, Status>>,
+}
+
+impl StreamingObserver {
+    pub fn new(sender: UnboundedSender<Result<CrawlEvent, Status>>) -> Self {
+        Self { sender }
+    }
+
+    fn send(&self, event: CrawlEvent) {
+        // The client may have gone away; the crawl carries on regardless.
+        let _ = self.sender.unbounded_send(Ok(event));
+    }
+}
+
+pub fn event(kind: CrawlEventKind, url: &str, depth: usize) -> CrawlEvent {
+    CrawlEvent {
+        kind: kind.into(),
+        url: url.to_string(),
+        depth: depth as u32,
+        message: None,
+        status_code: None,
+        bytes: None,
+    }
+}
+
+impl CrawlObserver for StreamingObserver {
+    fn on_scheduled(&self, url: &Url, depth: usize) {
+        self.send(event(CrawlEventKind::Scheduled, url.as_str(), depth));
+    }
+
+    fn on_fetched(&self, url: &Url, depth: usize, status: u16, bytes: usize) {
+        self.send(CrawlEvent {
+            status_code: Some(status as u32),
+            bytes: Some(bytes as u64),
+            ..event(CrawlEventKind::Fetched, url.as_str(), depth)
+        });
+    }
+
+    fn on_skipped(&self, url: &Url, depth: usize, reason: SkipReason) {
+        self.send(CrawlEvent {
+            message: Some(reason.to_string()),
+            ..event(CrawlEventKind::Skipped, url.as_str(), depth)
+        });
+    }
+
+    fn on_indexed(&self, url: &Url, depth: usize) {
+        self.send(event(CrawlEventKind::Indexed, url.as_str(), depth));
+    }
+
+    fn on_failed(&self, url: &Url, depth: usize, error: &str) {
+        self.send(CrawlEvent {
+            message: Some(error.to_string()),
+            ..event(CrawlEventKind::Failed, url.as_str(), depth)
+        });
+    }
+}
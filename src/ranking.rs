@@ -0,0 +1,118 @@
+//! Ranking signals blended with the BM25 score: link authority, crawl depth, freshness and URL
+//! length.
+
+use std::env;
+
+use tantivy::{DocId, Score, SegmentReader};
+
+use crate::search::RankingWeights;
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// Ranking signals of a document, read from its fast fields.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Signals {
+    pub authority: f64,
+    pub depth: f64,
+    // Days since the document was fetched, unless its fetch time is unknown.
+    pub age_days: Option<f64>,
+    pub url_length: f64,
+}
+
+/// Builds the function reading the ranking signals of the documents of a segment. `now` is in
+/// seconds since the Unix epoch.
+pub fn signal_reader(segment_reader: &SegmentReader, now: i64) -> impl Fn(DocId) -> Signals {
+    let fast_fields = segment_reader.fast_fields();
+    let authority = fast_fields.f64("authority").unwrap();
+    let depth = fast_fields.u64("depth").unwrap();
+    let fetched_at = fast_fields.date("fetched_at").unwrap();
+    let url_length = fast_fields.u64("url_length").unwrap();
+    move |doc| Signals {
+        authority: authority.first(doc).unwrap_or_default(),
+        depth: depth.first(doc).unwrap_or_default() as f64,
+        age_days: fetched_at.first(doc)
+            .map(|date| (now - date.into_timestamp_secs()).max(0) as f64 / SECONDS_PER_DAY),
+        url_length: url_length.first(doc).unwrap_or_default() as f64,
+    }
+}
+
+/// Weights of the ranking signals. A weight of zero disables its signal.
+#[derive(Clone, Debug)]
+pub struct RankingConfig {
+    // Boost by the logarithm of the PageRank authority.
+    pub authority: f64,
+    // Penalty per level of crawl depth.
+    pub depth: f64,
+    // Boost of a page fetched just now, halving every `freshness_half_life_days`.
+    pub freshness: f64,
+    pub freshness_half_life_days: f64,
+    // Boost of short URLs, inversely proportional to their length in tens of characters.
+    pub url_length: f64,
+}
+
+impl Default for RankingConfig {
+    fn default() -> Self {
+        Self {
+            authority: 0.5,
+            depth: 0.1,
+            freshness: 0.1,
+            freshness_half_life_days: 30.0,
+            url_length: 0.1,
+        }
+    }
+}
+
+impl RankingConfig {
+    /// Default weights, overridden by the `SEARCH_RANKING_AUTHORITY`, `SEARCH_RANKING_DEPTH`,
+    /// `SEARCH_RANKING_FRESHNESS`, `SEARCH_RANKING_FRESHNESS_HALF_LIFE_DAYS` and
+    /// `SEARCH_RANKING_URL_LENGTH` environment variables.
+    pub fn from_env() -> Result<Self, String> {
+        let weight = |name: &str| -> Result<Option<f64>, String> {
+            match env::var(name) {
+                Ok(value) => value.parse().map(Some).map_err(|_| format!("Invalid {}: {}", name, value)),
+                Err(_) => Ok(None),
+            }
+        };
+        Self::default().with_overrides(&RankingWeights {
+            authority: weight("SEARCH_RANKING_AUTHORITY")?,
+            depth: weight("SEARCH_RANKING_DEPTH")?,
+            freshness: weight("SEARCH_RANKING_FRESHNESS")?,
+            freshness_half_life_days: weight("SEARCH_RANKING_FRESHNESS_HALF_LIFE_DAYS")?,
+            url_length: weight("SEARCH_RANKING_URL_LENGTH")?,
+        })
+    }
+
+    /// Replaces the weights set in `overrides`.
+    pub fn with_overrides(&self, overrides: &RankingWeights) -> Result<Self, String> {
+        let config = Self {
+            authority: overrides.authority.unwrap_or(self.authority),
+            depth: overrides.depth.unwrap_or(self.depth),
+            freshness: overrides.freshness.unwrap_or(self.freshness),
+            freshness_half_life_days: overrides.freshness_half_life_days.unwrap_or(self.freshness_half_life_days),
+            url_length: overrides.url_length.unwrap_or(self.url_length),
+        };
+        if [config.authority, config.depth, config.freshness, config.url_length].iter().any(|weight| *weight < 0.0) {
+            return Err("Ranking weights must not be negative".to_string());
+        }
+        if config.freshness_half_life_days <= 0.0 {
+            return Err("Freshness half-life must be positive".to_string());
+        }
+        Ok(config)
+    }
+
+    /// Builds the function blending the BM25 score of the documents of a segment with their
+    /// ranking signals. `now` is in seconds since the Unix epoch.
+    pub fn scorer(&self, segment_reader: &SegmentReader, now: i64) -> impl Fn(DocId, Score) -> Score {
+        let config = self.clone();
+        let signals = signal_reader(segment_reader, now);
+        move |doc, score| {
+            let signals = signals(doc);
+            let freshness = signals.age_days.map_or(0.0, |age| 0.5f64.powf(age / config.freshness_half_life_days));
+            let boost = (1.0 + config.authority * signals.authority.max(0.0).ln_1p())
+                / (1.0 + config.depth * signals.depth)
+                * (1.0 + config.freshness * freshness)
+                * (1.0 + config.url_length / (1.0 + signals.url_length / 10.0));
+            score * boost as Score
+        }
+    }
+}
@@ -0,0 +1,536 @@
+//! Leader/follower replication: the leader appends the operations changing its index to a log,
+//! which followers stream and apply to their own index through the same `Writer` path.
+
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use futures::channel::mpsc;
+use futures::SinkExt;
+use prost::Message;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tonic::transport::Channel;
+use tonic::{Code, Status};
+
+use crate::collection::Collection;
+use crate::indexer::IndexerService;
+use crate::search::log_entry::Operation;
+use crate::search::searcher_client::SearcherClient;
+use crate::search::{ListCollectionsRequest, LogEntry, ReplicateRequest, ReplicatedLink, ReplicatedPage, ReplicationEvent, ReplicationRole, ReplicationStatus, SnapshotRequest};
+use crate::search_engine::{Link, Page};
+use crate::snapshot::Manifest;
+
+// Time between heartbeats of an idle replication stream.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+// Time a follower waits before reconnecting to its leader.
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+// Time between listings of the collections of the leader.
+const COLLECTIONS_INTERVAL: Duration = Duration::from_secs(5);
+// Events buffered per follower, so that slow followers hold back reading the log.
+const STREAM_BUFFER: usize = 64;
+/// Operations kept in the log before the last snapshot, for the followers lagging behind it.
+pub const RETAINED_OPERATIONS: u64 = 10_000;
+
+/// Role of the server. Servers lead unless they are given a leader to follow.
+#[derive(Clone, Debug, Default)]
+pub struct ReplicationConfig {
+    pub leader: Option<String>,
+}
+
+impl ReplicationConfig {
+    /// Follows the leader at the endpoint set by the `SEARCH_LEADER` environment variable.
+    pub fn from_env() -> Self {
+        Self { leader: env::var("SEARCH_LEADER").ok().filter(|leader| !leader.is_empty()) }
+    }
+}
+
+impl From<&Page> for ReplicatedPage {
+    fn from(page: &Page) -> Self {
+        Self {
+            url: page.url.clone(),
+            origin_url: page.origin_url.clone(),
+            depth: page.depth,
+            body: page.body.clone(),
+            text: page.text.clone(),
+            title: page.title.clone(),
+            description: page.description.clone(),
+            headings: page.headings.clone(),
+            language: page.language.clone(),
+            content_type: page.content_type.clone(),
+            fetched_at: page.fetched_at.duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs() as i64),
+            links: page.links.iter()
+                .map(|link| ReplicatedLink { url: link.url.clone(), anchor_text: link.anchor_text.clone() })
+                .collect(),
+        }
+    }
+}
+
+impl From<ReplicatedPage> for Page {
+    fn from(page: ReplicatedPage) -> Self {
+        Self {
+            url: page.url,
+            origin_url: page.origin_url,
+            depth: page.depth,
+            body: page.body,
+            text: page.text,
+            title: page.title,
+            description: page.description,
+            headings: page.headings,
+            language: page.language,
+            content_type: page.content_type,
+            fetched_at: UNIX_EPOCH + Duration::from_secs(page.fetched_at.max(0) as u64),
+            links: page.links.into_iter()
+                .map(|link| Link { url: link.url, anchor_text: link.anchor_text })
+                .collect(),
+        }
+    }
+}
+
+struct LogFile {
+    file: File,
+    // Sequence number of the last entry truncated from the log, 0 if none was.
+    first: u64,
+    // Offset of each entry in the file, by sequence number minus `first` minus one.
+    offsets: Vec<u64>,
+    end: u64,
+    // Incremented whenever a truncation replaces the file, which readers then reopen.
+    generation: u64,
+}
+
+/// Append-only file of length-delimited log entries, truncated up to the snapshots taken.
+pub struct ReplicationLog {
+    path: PathBuf,
+    file: Mutex<LogFile>,
+    // Sequence number of the last entry, watched by the replication streams.
+    sequence: watch::Sender<u64>,
+}
+
+impl ReplicationLog {
+    pub fn create(path: &Path) -> Self {
+        let file = OpenOptions::new().create(true).truncate(true).read(true).write(true).open(path)
+            .expect("Unable to create replication log");
+        Self {
+            path: path.to_path_buf(),
+            file: Mutex::new(LogFile { file, first: 0, offsets: Vec::new(), end: 0, generation: 0 }),
+            sequence: watch::Sender::new(0),
+        }
+    }
+
+    /// Opens a log written by another server, such as one restored from a snapshot, to append to
+    /// it. The log ends at the given sequence number.
+    pub fn open(path: &Path, sequence: u64) -> Result<Self, String> {
+        let file = OpenOptions::new().read(true).append(true).open(path)
+            .map_err(|e| format!("Failed to open the replication log. Error: {}", e))?;
+        let mut reader = BufReader::new(file.try_clone().map_err(|e| e.to_string())?);
+        let (mut offsets, mut end) = (Vec::new(), 0);
+        while let Some((prefix, length)) = read_length(&mut reader)? {
+            reader.seek_relative(length as i64).map_err(|e| e.to_string())?;
+            offsets.push(end);
+            end += (prefix + length) as u64;
+        }
+        let first = sequence.checked_sub(offsets.len() as u64)
+            .ok_or_else(|| format!("The replication log holds {} operations, more than its {}", offsets.len(), sequence))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            file: Mutex::new(LogFile { file, first, offsets, end, generation: 0 }),
+            sequence: watch::Sender::new(sequence),
+        })
+    }
+
+    /// The log file, and its size up to the last entry.
+    pub fn committed(&self) -> Result<(File, u64), String> {
+        let log = self.file.lock().unwrap();
+        let file = File::open(&self.path).map_err(|e| format!("Failed to read the replication log. Error: {}", e))?;
+        Ok((file, log.end))
+    }
+
+    /// Sequence number of the last entry, 0 if the log is empty.
+    pub fn sequence(&self) -> u64 {
+        *self.sequence.borrow()
+    }
+
+    /// Appends the operation and returns its sequence number. Callers append while holding the
+    /// writers of the operation, so that the log orders operations as the index does.
+    pub fn append(&self, operation: Operation) -> Result<u64, String> {
+        let mut log = self.file.lock().unwrap();
+        let sequence = log.first + log.offsets.len() as u64 + 1;
+        let committed_at = SystemTime::now().duration_since(UNIX_EPOCH).map_err(|e| e.to_string())?.as_millis() as i64;
+        let entry = LogEntry { sequence, committed_at, operation: Some(operation) };
+        let bytes = entry.encode_length_delimited_to_vec();
+        log.file.write_all(&bytes).map_err(|e| format!("Failed to append to the replication log. Error: {}", e))?;
+        let end = log.end;
+        log.offsets.push(end);
+        log.end += bytes.len() as u64;
+        self.sequence.send_replace(sequence);
+        Ok(sequence)
+    }
+
+    /// Drops the entries up to the given sequence number, which a snapshot covers. The entries
+    /// kept are copied to a new file, which replaces the log.
+    pub fn truncate(&self, through: u64) -> Result<(), String> {
+        let mut log = self.file.lock().unwrap();
+        let through = through.min(log.first + log.offsets.len() as u64);
+        if through <= log.first {
+            return Ok(());
+        }
+        let dropped = (through - log.first) as usize;
+        let start = log.offsets.get(dropped).copied().unwrap_or(log.end);
+        let failed = |e: io::Error| format!("Failed to truncate the replication log. Error: {}", e);
+        let truncated = self.path.with_extension("truncated");
+        let mut file = OpenOptions::new().create(true).truncate(true).read(true).write(true).open(&truncated).map_err(failed)?;
+        let mut kept = File::open(&self.path).map_err(failed)?;
+        kept.seek(SeekFrom::Start(start)).map_err(failed)?;
+        io::copy(&mut kept.take(log.end - start), &mut file).map_err(failed)?;
+        fs::rename(&truncated, &self.path).map_err(failed)?;
+        log.file = file;
+        log.first = through;
+        log.offsets = log.offsets[dropped..].iter().map(|offset| offset - start).collect();
+        log.end -= start;
+        log.generation += 1;
+        Ok(())
+    }
+
+    /// Opens the log for reading the entries following the given sequence number.
+    fn reader(&self, after: u64) -> Result<LogReader, String> {
+        let log = self.file.lock().unwrap();
+        if after < log.first {
+            return Err(format!("Operations up to {} were truncated from the log, restore a snapshot first", log.first));
+        }
+        let offset = match (after - log.first) as usize {
+            index if index < log.offsets.len() => log.offsets[index],
+            index if index == log.offsets.len() => log.end,
+            _ => return Err(format!("Sequence {} is ahead of the log, which ends at {}", after, log.first + log.offsets.len() as u64)),
+        };
+        let mut file = File::open(&self.path).map_err(|e| e.to_string())?;
+        file.seek(SeekFrom::Start(offset)).map_err(|e| e.to_string())?;
+        Ok(LogReader { file: BufReader::new(file), after, generation: log.generation })
+    }
+}
+
+struct LogReader {
+    file: BufReader<File>,
+    // Sequence number of the last entry read.
+    after: u64,
+    // Generation of the log file read.
+    generation: u64,
+}
+
+/// Reads the length prefix of the next entry, returning the size of the prefix and the length, or
+/// `None` at the end of the log.
+fn read_length(file: &mut impl Read) -> Result<Option<(usize, usize)>, String> {
+    let mut prefix = Vec::new();
+    let mut byte = [0u8];
+    loop {
+        match file.read(&mut byte).map_err(|e| e.to_string())? {
+            0 if prefix.is_empty() => return Ok(None),
+            0 => return Err("The replication log ends within an entry".to_string()),
+            _ => {}
+        }
+        prefix.push(byte[0]);
+        if byte[0] & 0x80 == 0 {
+            break;
+        }
+    }
+    let length = prost::decode_length_delimiter(prefix.as_slice()).map_err(|e| e.to_string())?;
+    Ok(Some((prefix.len(), length)))
+}
+
+impl LogReader {
+    /// Reads the next entry, which must have been fully appended. The log is reopened if it was
+    /// truncated since, as appends then go to the new file.
+    fn next(&mut self, log: &ReplicationLog) -> Result<LogEntry, String> {
+        if log.file.lock().unwrap().generation != self.generation {
+            *self = log.reader(self.after)?;
+        }
+        let (_, length) = read_length(&mut self.file)?.ok_or_else(|| "The replication log ends early".to_string())?;
+        let mut bytes = vec![0; length];
+        self.file.read_exact(&mut bytes).map_err(|e| e.to_string())?;
+        self.after += 1;
+        LogEntry::decode(bytes.as_slice()).map_err(|e| e.to_string())
+    }
+}
+
+/// Streams the entries of the log following the requested sequence number, then the entries
+/// appended later, as they are appended. Heartbeats keep idle streams alive and tell the follower
+/// the sequence number of the leader.
+pub fn stream(log: Arc<ReplicationLog>, request: ReplicateRequest) -> Result<mpsc::Receiver<Result<ReplicationEvent, Status>>, String> {
+    let mut reader = log.reader(request.after_sequence)?;
+    let (mut sender, receiver) = mpsc::channel(STREAM_BUFFER);
+    tokio::spawn(async move {
+        let mut sequence = log.sequence.subscribe();
+        let mut next = request.after_sequence + 1;
+        let mut heartbeat = true;
+        loop {
+            let leader_sequence = *sequence.borrow_and_update();
+            if heartbeat && sender.send(Ok(ReplicationEvent { leader_sequence, entry: None })).await.is_err() {
+                return;
+            }
+            while next <= leader_sequence {
+                let event = reader.next(&log)
+                    .map(|entry| ReplicationEvent { leader_sequence, entry: Some(entry) })
+                    .map_err(Status::internal);
+                let failed = event.is_err();
+                if sender.send(event).await.is_err() || failed {
+                    return;
+                }
+                next += 1;
+            }
+            heartbeat = match tokio::time::timeout(HEARTBEAT_INTERVAL, sequence.changed()).await {
+                Ok(Ok(())) => false,
+                Ok(Err(_)) => return,
+                Err(_) => true,
+            };
+        }
+    });
+    Ok(receiver)
+}
+
+struct FollowerState {
+    leader_sequence: u64,
+    connected: bool,
+    // Last time the follower had applied all operations of the leader.
+    caught_up_at: Instant,
+    message: Option<String>,
+}
+
+// Follower of a collection, and the task running it.
+type FollowerTask = (Arc<Follower>, JoinHandle<()>);
+
+/// Replica of every collection of a leader. Collections created on the leader are created with
+/// the same settings and followed, and collections dropped on the leader are dropped.
+pub struct Replica {
+    leader: String,
+    // Followers by collection name.
+    followers: Mutex<BTreeMap<String, FollowerTask>>,
+}
+
+impl Replica {
+    pub fn new(leader: String) -> Self {
+        Self { leader, followers: Mutex::new(BTreeMap::new()) }
+    }
+
+    pub fn leader(&self) -> &str {
+        &self.leader
+    }
+
+    /// Keeps following the collections of the leader, listing them periodically.
+    pub async fn follow(self: Arc<Self>, indexer: Arc<IndexerService>) {
+        loop {
+            if let Err(message) = self.follow_collections(&indexer).await {
+                tracing::warn!("Listing the collections of {} failed: {}", self.leader, message);
+            }
+            tokio::time::sleep(COLLECTIONS_INTERVAL).await;
+        }
+    }
+
+    async fn follow_collections(&self, indexer: &Arc<IndexerService>) -> Result<(), String> {
+        let mut client = SearcherClient::connect(self.leader.clone()).await.map_err(|e| e.to_string())?;
+        let collections = client.list_collections(ListCollectionsRequest {}).await
+            .map_err(|status| status.message().to_string())?
+            .into_inner()
+            .collections;
+        let mut followers = self.followers.lock().unwrap();
+        followers.retain(|name, (_, task)| {
+            let dropped = !collections.iter().any(|collection| &collection.name == name);
+            if dropped {
+                task.abort();
+                if let Err(message) = indexer.drop_collection(name) {
+                    tracing::warn!("Dropping collection {} failed: {}", name, message);
+                }
+            }
+            !dropped
+        });
+        for collection in collections {
+            if followers.contains_key(&collection.name) {
+                continue;
+            }
+            if indexer.collection(&collection.name).is_err() {
+                indexer.create_collection(&collection.name, &collection.options.unwrap_or_default())?;
+            }
+            let follower = Arc::new(Follower::new(self.leader.clone(), collection.name.clone()));
+            let task = tokio::spawn(follower.clone().follow(indexer.clone()));
+            followers.insert(collection.name, (follower, task));
+        }
+        Ok(())
+    }
+
+    /// Replication status of the collection, whose sequence number is given.
+    pub fn status(&self, collection: &str, sequence: u64) -> ReplicationStatus {
+        match self.followers.lock().unwrap().get(collection) {
+            Some((follower, _)) => follower.status(sequence),
+            None => ReplicationStatus {
+                role: ReplicationRole::Follower.into(),
+                leader: Some(self.leader.clone()),
+                sequence,
+                message: Some(format!("Collection {} is not replicated yet", collection)),
+                ..Default::default()
+            }
+        }
+    }
+}
+
+/// Replica of a collection of a leader, kept up to date by applying its log.
+pub struct Follower {
+    leader: String,
+    collection: String,
+    state: Mutex<FollowerState>,
+}
+
+impl Follower {
+    pub fn new(leader: String, collection: String) -> Self {
+        Self {
+            leader,
+            collection,
+            state: Mutex::new(FollowerState { leader_sequence: 0, connected: false, caught_up_at: Instant::now(), message: None }),
+        }
+    }
+
+    /// Applies the log of the leader to the index, reconnecting whenever the stream breaks.
+    pub async fn follow(self: Arc<Self>, indexer: Arc<IndexerService>) {
+        loop {
+            if let Err(message) = self.replicate(&indexer).await {
+                tracing::warn!("Replication of {} from {} failed: {}", self.collection, self.leader, message);
+                let mut state = self.state.lock().unwrap();
+                state.connected = false;
+                state.message = Some(message);
+            }
+            tokio::time::sleep(RETRY_DELAY).await;
+        }
+    }
+
+    async fn replicate(&self, indexer: &IndexerService) -> Result<(), String> {
+        let mut client = SearcherClient::connect(self.leader.clone()).await.map_err(|e| e.to_string())?;
+        let collection = indexer.collection(&self.collection)?;
+        let mut request = ReplicateRequest {
+            after_sequence: collection.search_engine().replication_log().sequence(),
+            collection: self.collection.clone(),
+        };
+        let mut events = match client.replicate(request.clone()).await {
+            Ok(response) => response.into_inner(),
+            // The log of the leader no longer holds the operations following the last one applied,
+            // such as after a snapshot: the follower starts over from a snapshot of the leader.
+            Err(status) if status.code() == Code::OutOfRange => {
+                tracing::info!("Restoring a snapshot of {} from {}: {}", self.collection, self.leader, status.message());
+                request.after_sequence = self.bootstrap(&mut client, collection.clone()).await?.sequence;
+                client.replicate(request).await.map_err(|status| status.message().to_string())?.into_inner()
+            }
+            Err(status) => return Err(status.message().to_string()),
+        };
+        let search_engine = collection.search_engine();
+        self.state.lock().unwrap().connected = true;
+        while let Some(event) = events.message().await.map_err(|status| status.message().to_string())? {
+            if let Some(entry) = event.entry {
+                // Applying commits the index, and may recompute the link signals of all pages.
+                let search_engine = search_engine.clone();
+                tokio::task::spawn_blocking(move || search_engine.apply(entry)).await.map_err(|e| e.to_string())??;
+            }
+            let sequence = search_engine.replication_log().sequence();
+            let mut state = self.state.lock().unwrap();
+            state.leader_sequence = event.leader_sequence;
+            if sequence >= event.leader_sequence {
+                state.caught_up_at = Instant::now();
+            }
+            state.message = None;
+        }
+        Err("The leader closed the replication stream".to_string())
+    }
+
+    /// Replaces the index of the collection with a snapshot streamed from the leader.
+    async fn bootstrap(&self, client: &mut SearcherClient<Channel>, collection: Arc<Collection>) -> Result<Manifest, String> {
+        let request = SnapshotRequest { collection: self.collection.clone() };
+        let mut chunks = client.snapshot(request).await.map_err(|status| status.message().to_string())?.into_inner();
+        let mut archive = tempfile::tempfile().map_err(|e| e.to_string())?;
+        while let Some(chunk) = chunks.message().await.map_err(|status| status.message().to_string())? {
+            archive.write_all(&chunk.data).map_err(|e| e.to_string())?;
+        }
+        archive.rewind().map_err(|e| e.to_string())?;
+        let manifest = tokio::task::spawn_blocking(move || collection.replace(archive)).await.map_err(|e| e.to_string())??;
+        tracing::info!("Restored snapshot of {} at sequence {} of {} shards from {}", self.collection, manifest.sequence, manifest.shards, self.leader);
+        Ok(manifest)
+    }
+
+    pub fn status(&self, sequence: u64) -> ReplicationStatus {
+        let state = self.state.lock().unwrap();
+        let lag_operations = state.leader_sequence.saturating_sub(sequence);
+        ReplicationStatus {
+            role: ReplicationRole::Follower.into(),
+            leader: Some(self.leader.clone()),
+            sequence,
+            leader_sequence: state.leader_sequence,
+            lag_operations,
+            lag_millis: if lag_operations == 0 { 0 } else { state.caught_up_at.elapsed().as_millis() as u64 },
+            connected: state.connected,
+            message: state.message.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::AnalyzeLinks;
+
+    fn append(log: &ReplicationLog, operations: usize) {
+        for _ in 0..operations {
+            log.append(Operation::AnalyzeLinks(AnalyzeLinks {})).unwrap();
+        }
+    }
+
+    fn read(reader: &mut LogReader, log: &ReplicationLog, entries: usize) -> Vec<u64> {
+        (0..entries).map(|_| reader.next(log).unwrap().sequence).collect()
+    }
+
+    #[test]
+    fn truncation_keeps_the_following_entries() {
+        let directory = tempfile::tempdir().unwrap();
+        let log = ReplicationLog::create(&directory.path().join("replication.log"));
+        append(&log, 10);
+        log.truncate(4).unwrap();
+        assert_eq!(log.sequence(), 10);
+        assert!(log.reader(3).is_err());
+        assert_eq!(read(&mut log.reader(4).unwrap(), &log, 6), (5..=10).collect::<Vec<_>>());
+        assert_eq!(read(&mut log.reader(8).unwrap(), &log, 2), vec![9, 10]);
+        // Entries already truncated are not truncated again.
+        log.truncate(2).unwrap();
+        assert_eq!(read(&mut log.reader(4).unwrap(), &log, 6), (5..=10).collect::<Vec<_>>());
+        assert_eq!(log.append(Operation::AnalyzeLinks(AnalyzeLinks {})).unwrap(), 11);
+        assert!(log.reader(12).is_err());
+    }
+
+    #[test]
+    fn readers_reopen_truncated_logs() {
+        let directory = tempfile::tempdir().unwrap();
+        let log = ReplicationLog::create(&directory.path().join("replication.log"));
+        append(&log, 10);
+        let mut reader = log.reader(2).unwrap();
+        assert_eq!(read(&mut reader, &log, 3), vec![3, 4, 5]);
+        log.truncate(5).unwrap();
+        append(&log, 2);
+        assert_eq!(read(&mut reader, &log, 7), (6..=12).collect::<Vec<_>>());
+        let mut behind = log.reader(5).unwrap();
+        log.truncate(8).unwrap();
+        assert!(behind.next(&log).is_err());
+    }
+
+    #[test]
+    fn opens_logs_starting_after_truncated_entries() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("replication.log");
+        let log = ReplicationLog::create(&path);
+        append(&log, 10);
+        log.truncate(6).unwrap();
+        drop(log);
+        assert!(ReplicationLog::open(&path, 3).is_err());
+        let log = ReplicationLog::open(&path, 10).unwrap();
+        assert_eq!(log.sequence(), 10);
+        assert!(log.reader(5).is_err());
+        assert_eq!(read(&mut log.reader(6).unwrap(), &log, 4), vec![7, 8, 9, 10]);
+        assert_eq!(log.append(Operation::AnalyzeLinks(AnalyzeLinks {})).unwrap(), 11);
+        assert_eq!(read(&mut log.reader(9).unwrap(), &log, 2), vec![10, 11]);
+    }
+}
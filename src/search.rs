@@ -5,6 +5,9 @@ pub struct IndexRequest {
     pub origin: ::prost::alloc::string::String,
     #[prost(uint32, tag = "2")]
     pub k: u32,
+    /// Name of the collection the pages are indexed in, the default collection when empty.
+    #[prost(string, tag = "3")]
+    pub collection: ::prost::alloc::string::String,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -16,35 +19,887 @@ pub struct IndexResponse {
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CrawlEvent {
+    #[prost(enumeration = "CrawlEventKind", tag = "1")]
+    pub kind: i32,
+    #[prost(string, tag = "2")]
+    pub url: ::prost::alloc::string::String,
+    #[prost(uint32, tag = "3")]
+    pub depth: u32,
+    #[prost(string, optional, tag = "4")]
+    pub message: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(uint32, optional, tag = "5")]
+    pub status_code: ::core::option::Option<u32>,
+    #[prost(uint64, optional, tag = "6")]
+    pub bytes: ::core::option::Option<u64>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SearchRequest {
     #[prost(string, tag = "1")]
     pub query: ::prost::alloc::string::String,
+    /// ISO 639-1 code of the language to restrict results to.
+    #[prost(string, optional, tag = "2")]
+    pub language: ::core::option::Option<::prost::alloc::string::String>,
+    /// Overrides of the boosts of the default search fields, by field name.
+    #[prost(map = "string, float", tag = "3")]
+    pub field_boosts: ::std::collections::HashMap<::prost::alloc::string::String, f32>,
+    /// How the terms of the query are combined when no operator is given.
+    #[prost(enumeration = "QueryOperator", tag = "4")]
+    pub operator: i32,
+    #[prost(message, optional, tag = "5")]
+    pub filters: ::core::option::Option<SearchFilters>,
+    /// Facets to count the matching documents of.
+    #[prost(enumeration = "FacetKind", repeated, tag = "6")]
+    pub facets: ::prost::alloc::vec::Vec<i32>,
+    /// Maximum number of buckets per facet, 10 by default.
+    #[prost(uint32, optional, tag = "7")]
+    pub facet_size: ::core::option::Option<u32>,
+    /// Order of the results, and of results with equal primary sort values.
+    #[prost(enumeration = "SortBy", tag = "8")]
+    pub sort_by: i32,
+    #[prost(enumeration = "SortBy", tag = "9")]
+    pub then_by: i32,
+    /// Whether terms also match with a few typos, more for longer terms.
+    #[prost(bool, tag = "10")]
+    pub fuzzy: bool,
+    /// Overrides of the server's ranking weights.
+    #[prost(message, optional, tag = "11")]
+    pub ranking: ::core::option::Option<RankingWeights>,
+    /// Expression computing the final score of the results from their ranked `score` and the
+    /// `depth`, `authority`, `url_length` and `age_days` of their page, such as
+    /// `score / (1 + depth) + 0.2 * log(authority)`.
+    #[prost(string, optional, tag = "12")]
+    pub score_expression: ::core::option::Option<::prost::alloc::string::String>,
+    /// Limits the results per host or per cluster of duplicate pages.
+    #[prost(message, optional, tag = "13")]
+    pub collapse: ::core::option::Option<Collapse>,
+    /// Number of results to skip, and to return after them, 10 by default.
+    #[prost(uint32, tag = "14")]
+    pub offset: u32,
+    #[prost(uint32, optional, tag = "15")]
+    pub limit: ::core::option::Option<u32>,
+    #[prost(enumeration = "SearchMode", tag = "16")]
+    pub mode: i32,
+    /// How hybrid searches merge the candidates of their retrievers.
+    #[prost(message, optional, tag = "17")]
+    pub fusion: ::core::option::Option<Fusion>,
+    /// Whether keyword searches match the query against passages of the visible text of pages,
+    /// ranking pages by their best passage. Field boosts, fuzziness and language analysis do not
+    /// apply.
+    #[prost(bool, tag = "18")]
+    pub passages: bool,
+    /// Whether a federating server searches its own index only, without forwarding the request to
+    /// its peers. Set on the requests it forwards.
+    #[prost(bool, tag = "19")]
+    pub local: bool,
+    /// Name of the collection searched, the default collection when empty.
+    #[prost(string, tag = "20")]
+    pub collection: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Fusion {
+    #[prost(enumeration = "FusionMethod", tag = "1")]
+    pub method: i32,
+    /// Rank constant of reciprocal rank fusion, 60 by default.
+    #[prost(double, optional, tag = "2")]
+    pub rrf_k: ::core::option::Option<f64>,
+    /// Weights of the retrievers in weighted score blending, 0.5 by default. Keyword scores are
+    /// scaled to the best one, semantic scores are similarities between 0 and 1.
+    #[prost(double, optional, tag = "3")]
+    pub keyword_weight: ::core::option::Option<f64>,
+    #[prost(double, optional, tag = "4")]
+    pub semantic_weight: ::core::option::Option<f64>,
+}
+/// Finds the pages similar to an indexed page, or to a text.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MoreLikeThisRequest {
+    /// ISO 639-1 code of the language to restrict results to.
+    #[prost(string, optional, tag = "3")]
+    pub language: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(message, optional, tag = "4")]
+    pub filters: ::core::option::Option<SearchFilters>,
+    #[prost(uint32, tag = "5")]
+    pub offset: u32,
+    #[prost(uint32, optional, tag = "6")]
+    pub limit: ::core::option::Option<u32>,
+    /// Name of the collection searched, the default collection when empty.
+    #[prost(string, tag = "7")]
+    pub collection: ::prost::alloc::string::String,
+    #[prost(oneof = "more_like_this_request::Like", tags = "1, 2")]
+    pub like: ::core::option::Option<more_like_this_request::Like>,
+}
+/// Nested message and enum types in `MoreLikeThisRequest`.
+pub mod more_like_this_request {
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Like {
+        #[prost(string, tag = "1")]
+        Url(::prost::alloc::string::String),
+        #[prost(string, tag = "2")]
+        Text(::prost::alloc::string::String),
+    }
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Collapse {
+    #[prost(enumeration = "CollapseBy", tag = "1")]
+    pub by: i32,
+    /// Maximum number of results per group, 1 when unset.
+    #[prost(uint32, tag = "2")]
+    pub max_per_group: u32,
+}
+/// Weights of the signals blended with the BM25 score. Unset weights keep the server's value.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RankingWeights {
+    #[prost(double, optional, tag = "1")]
+    pub authority: ::core::option::Option<f64>,
+    #[prost(double, optional, tag = "2")]
+    pub depth: ::core::option::Option<f64>,
+    #[prost(double, optional, tag = "3")]
+    pub freshness: ::core::option::Option<f64>,
+    #[prost(double, optional, tag = "4")]
+    pub freshness_half_life_days: ::core::option::Option<f64>,
+    #[prost(double, optional, tag = "5")]
+    pub url_length: ::core::option::Option<f64>,
+}
+/// Restrictions on the documents matched by a search. Unset fields do not restrict.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SearchFilters {
+    #[prost(string, optional, tag = "1")]
+    pub origin_url: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "2")]
+    pub host: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(uint32, optional, tag = "3")]
+    pub min_depth: ::core::option::Option<u32>,
+    #[prost(uint32, optional, tag = "4")]
+    pub max_depth: ::core::option::Option<u32>,
+    /// MIME type without parameters, such as `text/html`.
+    #[prost(string, optional, tag = "5")]
+    pub content_type: ::core::option::Option<::prost::alloc::string::String>,
+    /// Bounds of the fetch time in seconds since the Unix epoch, inclusive and exclusive.
+    #[prost(int64, optional, tag = "6")]
+    pub fetched_after: ::core::option::Option<i64>,
+    #[prost(int64, optional, tag = "7")]
+    pub fetched_before: ::core::option::Option<i64>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SearchResponse {
+    #[prost(enumeration = "ResponseStatus", tag = "1")]
+    pub status: i32,
+    #[prost(string, optional, tag = "2")]
+    pub message: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(message, repeated, tag = "3")]
+    pub results: ::prost::alloc::vec::Vec<SearchResult>,
+    #[prost(message, repeated, tag = "4")]
+    pub facets: ::prost::alloc::vec::Vec<FacetResult>,
+    #[prost(uint64, tag = "5")]
+    pub total_hits: u64,
+    /// Corrected query, returned when the search has few or no hits.
+    #[prost(string, optional, tag = "6")]
+    pub suggestion: ::core::option::Option<::prost::alloc::string::String>,
+    /// Peers of a federated search that failed or timed out, whose results are missing.
+    #[prost(message, repeated, tag = "7")]
+    pub failed_peers: ::prost::alloc::vec::Vec<PeerFailure>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PeerFailure {
+    /// Endpoint of the peer, such as `<http://\[::1\]:50052`.>
+    #[prost(string, tag = "1")]
+    pub peer: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub message: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FacetResult {
+    #[prost(enumeration = "FacetKind", tag = "1")]
+    pub kind: i32,
+    /// Buckets with the most matching documents first.
+    #[prost(message, repeated, tag = "2")]
+    pub buckets: ::prost::alloc::vec::Vec<FacetBucket>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FacetBucket {
+    #[prost(string, tag = "1")]
+    pub value: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "2")]
+    pub count: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SearchResult {
+    #[prost(string, tag = "1")]
+    pub relevant_url: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub origin_url: ::prost::alloc::string::String,
+    #[prost(uint32, tag = "3")]
+    pub depth: u32,
+    #[prost(string, tag = "4")]
+    pub language: ::prost::alloc::string::String,
+    #[prost(string, tag = "5")]
+    pub title: ::prost::alloc::string::String,
+    #[prost(string, tag = "6")]
+    pub content_type: ::prost::alloc::string::String,
+    /// Seconds since the Unix epoch.
+    #[prost(int64, tag = "7")]
+    pub fetched_at: i64,
+    #[prost(float, tag = "8")]
+    pub score: f32,
+    /// Number of hits of the collapsed group of the result that are not returned, set on the first
+    /// result of the group.
+    #[prost(uint64, tag = "9")]
+    pub collapsed_hits: u64,
+    /// Retrievers that found the result.
+    #[prost(enumeration = "Retriever", repeated, tag = "10")]
+    pub retrievers: ::prost::alloc::vec::Vec<i32>,
+    /// Passage of the visible text best matching the query, for passage searches.
+    #[prost(message, optional, tag = "11")]
+    pub passage: ::core::option::Option<Passage>,
+    /// Peer the result comes from in federated searches, empty for results of this server.
+    #[prost(string, tag = "12")]
+    pub peer: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Passage {
+    #[prost(string, tag = "1")]
+    pub text: ::prost::alloc::string::String,
+    /// Offset of the passage in the visible text, in characters.
+    #[prost(uint64, tag = "2")]
+    pub offset: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CacheStatsRequest {
+    /// Name of the collection whose cache is described, the default collection when empty.
+    #[prost(string, tag = "1")]
+    pub collection: ::prost::alloc::string::String,
+}
+/// Counters of the search result cache since the server started, and its current size.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CacheStats {
+    #[prost(uint64, tag = "1")]
+    pub hits: u64,
+    #[prost(uint64, tag = "2")]
+    pub misses: u64,
+    /// Responses dropped to stay within the memory bound.
+    #[prost(uint64, tag = "3")]
+    pub evictions: u64,
+    /// Times the cache was emptied because the index changed.
+    #[prost(uint64, tag = "4")]
+    pub invalidations: u64,
+    #[prost(uint64, tag = "5")]
+    pub entries: u64,
+    #[prost(uint64, tag = "6")]
+    pub bytes: u64,
+}
+/// Settings of a collection. Unset settings keep the server's value.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CollectionOptions {
+    /// Number of shards of the index.
+    #[prost(uint32, optional, tag = "1")]
+    pub shards: ::core::option::Option<u32>,
+    /// Size of the indexed passages in words, 0 to not index passages.
+    #[prost(uint32, optional, tag = "2")]
+    pub passage_words: ::core::option::Option<u32>,
+    /// Words shared by consecutive passages.
+    #[prost(uint32, optional, tag = "3")]
+    pub passage_overlap: ::core::option::Option<u32>,
+    /// Maximum number of pages fetched per crawl.
+    #[prost(uint32, optional, tag = "4")]
+    pub max_pages: ::core::option::Option<u32>,
+    /// Maximum number of requests a crawl sends at once.
+    #[prost(uint32, optional, tag = "5")]
+    pub max_concurrent_requests: ::core::option::Option<u32>,
+    /// Whether crawls obey robots.txt.
+    #[prost(bool, optional, tag = "6")]
+    pub robots: ::core::option::Option<bool>,
+    /// Boosts of the default search fields, by field name, overriding the server's. Searches may
+    /// override them in turn.
+    #[prost(map = "string, float", tag = "7")]
+    pub field_boosts: ::std::collections::HashMap<::prost::alloc::string::String, f32>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateCollectionRequest {
+    /// Letters, digits, `-` and `_`, at most 64 characters.
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub options: ::core::option::Option<CollectionOptions>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListCollectionsRequest {}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DropCollectionRequest {
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CollectionInfo {
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    /// Settings of the collection, with the server's values filled in.
+    #[prost(message, optional, tag = "2")]
+    pub options: ::core::option::Option<CollectionOptions>,
+    /// Number of operations written to the collection.
+    #[prost(uint64, tag = "3")]
+    pub sequence: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CollectionResponse {
+    #[prost(enumeration = "ResponseStatus", tag = "1")]
+    pub status: i32,
+    #[prost(string, optional, tag = "2")]
+    pub message: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(message, optional, tag = "3")]
+    pub collection: ::core::option::Option<CollectionInfo>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListCollectionsResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub collections: ::prost::alloc::vec::Vec<CollectionInfo>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReplicateRequest {
+    /// Sequence number of the last operation the follower applied, 0 if none.
+    #[prost(uint64, tag = "1")]
+    pub after_sequence: u64,
+    /// Name of the collection replicated, the default collection when empty.
+    #[prost(string, tag = "2")]
+    pub collection: ::prost::alloc::string::String,
+}
+/// An operation of the replication log, or a heartbeat announcing the sequence number of the last
+/// operation of the leader.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReplicationEvent {
+    #[prost(uint64, tag = "1")]
+    pub leader_sequence: u64,
+    #[prost(message, optional, tag = "2")]
+    pub entry: ::core::option::Option<LogEntry>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LogEntry {
+    /// Sequence numbers start at 1, with no gaps.
+    #[prost(uint64, tag = "1")]
+    pub sequence: u64,
+    /// Milliseconds since the Unix epoch.
+    #[prost(int64, tag = "2")]
+    pub committed_at: i64,
+    #[prost(oneof = "log_entry::Operation", tags = "3, 4")]
+    pub operation: ::core::option::Option<log_entry::Operation>,
+}
+/// Nested message and enum types in `LogEntry`.
+pub mod log_entry {
+    #[allow(clippy::large_enum_variant)]
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Operation {
+        #[prost(message, tag = "3")]
+        Put(super::ReplicatedPage),
+        #[prost(message, tag = "4")]
+        AnalyzeLinks(super::AnalyzeLinks),
+    }
+}
+/// A crawled page, as written to the index.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReplicatedPage {
+    #[prost(string, tag = "1")]
+    pub url: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub origin_url: ::prost::alloc::string::String,
+    #[prost(uint32, tag = "3")]
+    pub depth: u32,
+    #[prost(string, tag = "4")]
+    pub body: ::prost::alloc::string::String,
+    #[prost(string, tag = "5")]
+    pub title: ::prost::alloc::string::String,
+    #[prost(string, tag = "6")]
+    pub description: ::prost::alloc::string::String,
+    #[prost(string, repeated, tag = "7")]
+    pub headings: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(string, tag = "8")]
+    pub language: ::prost::alloc::string::String,
+    #[prost(string, tag = "9")]
+    pub content_type: ::prost::alloc::string::String,
+    /// Seconds since the Unix epoch.
+    #[prost(int64, tag = "10")]
+    pub fetched_at: i64,
+    #[prost(message, repeated, tag = "11")]
+    pub links: ::prost::alloc::vec::Vec<ReplicatedLink>,
+    /// Visible text of the body, without markup, scripts and styles.
+    #[prost(string, tag = "12")]
+    pub text: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReplicatedLink {
+    #[prost(string, tag = "1")]
+    pub url: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub anchor_text: ::prost::alloc::string::String,
+}
+/// Recomputation of the link signals of all pages, run after each crawl.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AnalyzeLinks {}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReplicationStatusRequest {
+    /// Name of the collection, the default collection when empty.
+    #[prost(string, tag = "1")]
+    pub collection: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReplicationStatus {
+    #[prost(enumeration = "ReplicationRole", tag = "1")]
+    pub role: i32,
+    /// Endpoint of the leader of a follower.
+    #[prost(string, optional, tag = "2")]
+    pub leader: ::core::option::Option<::prost::alloc::string::String>,
+    /// Sequence number of the last operation written by a leader or applied by a follower.
+    #[prost(uint64, tag = "3")]
+    pub sequence: u64,
+    /// Sequence number of the last operation of the leader, as last heard of by a follower.
+    #[prost(uint64, tag = "4")]
+    pub leader_sequence: u64,
+    /// Operations of the leader the follower has not applied yet.
+    #[prost(uint64, tag = "5")]
+    pub lag_operations: u64,
+    /// Time since the follower was last caught up with the leader, 0 while it is.
+    #[prost(uint64, tag = "6")]
+    pub lag_millis: u64,
+    /// Whether the follower is receiving the log of the leader.
+    #[prost(bool, tag = "7")]
+    pub connected: bool,
+    /// Last replication error of the follower.
+    #[prost(string, optional, tag = "8")]
+    pub message: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SnapshotRequest {
+    /// Name of the collection archived, the default collection when empty.
+    #[prost(string, tag = "1")]
+    pub collection: ::prost::alloc::string::String,
+}
+/// A piece of a snapshot archive: a tar file starting with a `MANIFEST` entry.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SnapshotChunk {
+    #[prost(bytes = "vec", tag = "1")]
+    pub data: ::prost::alloc::vec::Vec<u8>,
+    /// Name of the collection restored into, the default collection when empty. Only read from the
+    /// first chunk of a restore.
+    #[prost(string, tag = "2")]
+    pub collection: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RestoreResponse {
+    #[prost(enumeration = "ResponseStatus", tag = "1")]
+    pub status: i32,
+    #[prost(string, optional, tag = "2")]
+    pub message: ::core::option::Option<::prost::alloc::string::String>,
+    /// Sequence number of the last operation of the restored replication log.
+    #[prost(uint64, tag = "3")]
+    pub sequence: u64,
+    /// Number of shards of the restored index, which is that of the snapshot.
+    #[prost(uint32, tag = "4")]
+    pub shards: u32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ExportRequest {
+    /// Name of the collection exported, the default collection when empty.
+    #[prost(string, tag = "1")]
+    pub collection: ::prost::alloc::string::String,
+}
+/// A piece of JSON Lines, which may end within a line.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DocumentChunk {
+    #[prost(bytes = "vec", tag = "1")]
+    pub data: ::prost::alloc::vec::Vec<u8>,
+    /// Name of the collection imported into, the default collection when empty. Only read from the
+    /// first chunk of an import.
+    #[prost(string, tag = "2")]
+    pub collection: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ImportResponse {
+    #[prost(enumeration = "ResponseStatus", tag = "1")]
+    pub status: i32,
+    #[prost(string, optional, tag = "2")]
+    pub message: ::core::option::Option<::prost::alloc::string::String>,
+    /// Number of pages imported.
+    #[prost(uint64, tag = "3")]
+    pub pages: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SuggestRequest {
+    #[prost(string, tag = "1")]
+    pub prefix: ::prost::alloc::string::String,
+    /// Maximum number of suggestions, 10 by default.
+    #[prost(uint32, optional, tag = "2")]
+    pub limit: ::core::option::Option<u32>,
+    /// Name of the collection suggestions come from, the default collection when empty.
+    #[prost(string, tag = "3")]
+    pub collection: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SuggestResponse {
+    #[prost(enumeration = "ResponseStatus", tag = "1")]
+    pub status: i32,
+    #[prost(string, optional, tag = "2")]
+    pub message: ::core::option::Option<::prost::alloc::string::String>,
+    /// Most frequent first.
+    #[prost(message, repeated, tag = "3")]
+    pub suggestions: ::prost::alloc::vec::Vec<Suggestion>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Suggestion {
+    #[prost(string, tag = "1")]
+    pub text: ::prost::alloc::string::String,
+    #[prost(enumeration = "SuggestionSource", tag = "2")]
+    pub source: i32,
+    /// Number of past queries, or of documents with the title or term.
+    #[prost(uint64, tag = "3")]
+    pub frequency: u64,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum CrawlEventKind {
+    Scheduled = 0,
+    Fetched = 1,
+    Skipped = 2,
+    Indexed = 3,
+    Failed = 4,
+    Finished = 5,
+}
+impl CrawlEventKind {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            CrawlEventKind::Scheduled => "Scheduled",
+            CrawlEventKind::Fetched => "Fetched",
+            CrawlEventKind::Skipped => "Skipped",
+            CrawlEventKind::Indexed => "Indexed",
+            CrawlEventKind::Failed => "Failed",
+            CrawlEventKind::Finished => "Finished",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "Scheduled" => Some(Self::Scheduled),
+            "Fetched" => Some(Self::Fetched),
+            "Skipped" => Some(Self::Skipped),
+            "Indexed" => Some(Self::Indexed),
+            "Failed" => Some(Self::Failed),
+            "Finished" => Some(Self::Finished),
+            _ => None,
+        }
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum FusionMethod {
+    /// Sums 1 / (rrf_k + rank) over the retrievers that found the page.
+    ReciprocalRank = 0,
+    /// Sums the weighted scores of the retrievers that found the page.
+    WeightedScore = 1,
+}
+impl FusionMethod {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            FusionMethod::ReciprocalRank => "FusionMethodReciprocalRank",
+            FusionMethod::WeightedScore => "FusionMethodWeightedScore",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "FusionMethodReciprocalRank" => Some(Self::ReciprocalRank),
+            "FusionMethodWeightedScore" => Some(Self::WeightedScore),
+            _ => None,
+        }
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum Retriever {
+    Keyword = 0,
+    Semantic = 1,
+}
+impl Retriever {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Retriever::Keyword => "RetrieverKeyword",
+            Retriever::Semantic => "RetrieverSemantic",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "RetrieverKeyword" => Some(Self::Keyword),
+            "RetrieverSemantic" => Some(Self::Semantic),
+            _ => None,
+        }
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum SearchMode {
+    /// Matches the terms of the query.
+    Keyword = 0,
+    /// Matches the pages closest in meaning to the query, among the nearest neighbours of its
+    /// embedding. Filters apply to these neighbours only.
+    Semantic = 1,
+    /// Merges the candidates of the keyword and semantic searches.
+    Hybrid = 2,
+}
+impl SearchMode {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            SearchMode::Keyword => "Keyword",
+            SearchMode::Semantic => "Semantic",
+            SearchMode::Hybrid => "Hybrid",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "Keyword" => Some(Self::Keyword),
+            "Semantic" => Some(Self::Semantic),
+            "Hybrid" => Some(Self::Hybrid),
+            _ => None,
+        }
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum CollapseBy {
+    Host = 0,
+    /// Pages with the same body, ignoring case and whitespace.
+    ContentHash = 1,
+}
+impl CollapseBy {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            CollapseBy::Host => "CollapseByHost",
+            CollapseBy::ContentHash => "CollapseByContentHash",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "CollapseByHost" => Some(Self::Host),
+            "CollapseByContentHash" => Some(Self::ContentHash),
+            _ => None,
+        }
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum SortBy {
+    /// Highest score first.
+    Relevance = 0,
+    /// Newest first.
+    FetchedAt = 1,
+    /// Shallowest first.
+    Depth = 2,
+    /// Alphabetical.
+    Url = 3,
+}
+impl SortBy {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            SortBy::Relevance => "Relevance",
+            SortBy::FetchedAt => "FetchedAt",
+            SortBy::Depth => "Depth",
+            SortBy::Url => "Url",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "Relevance" => Some(Self::Relevance),
+            "FetchedAt" => Some(Self::FetchedAt),
+            "Depth" => Some(Self::Depth),
+            "Url" => Some(Self::Url),
+            _ => None,
+        }
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum FacetKind {
+    Host = 0,
+    Origin = 1,
+    Language = 2,
+    ContentType = 3,
+}
+impl FacetKind {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            FacetKind::Host => "Host",
+            FacetKind::Origin => "Origin",
+            FacetKind::Language => "Language",
+            FacetKind::ContentType => "ContentType",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "Host" => Some(Self::Host),
+            "Origin" => Some(Self::Origin),
+            "Language" => Some(Self::Language),
+            "ContentType" => Some(Self::ContentType),
+            _ => None,
+        }
+    }
 }
-#[allow(clippy::derive_partial_eq_without_eq)]
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct SearchResponse {
-    #[prost(enumeration = "ResponseStatus", tag = "1")]
-    pub status: i32,
-    #[prost(string, optional, tag = "2")]
-    pub message: ::core::option::Option<::prost::alloc::string::String>,
-    #[prost(message, repeated, tag = "3")]
-    pub results: ::prost::alloc::vec::Vec<SearchResult>,
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum QueryOperator {
+    Or = 0,
+    And = 1,
 }
-#[allow(clippy::derive_partial_eq_without_eq)]
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct SearchResult {
-    #[prost(string, tag = "1")]
-    pub relevant_url: ::prost::alloc::string::String,
-    #[prost(string, tag = "2")]
-    pub origin_url: ::prost::alloc::string::String,
-    #[prost(uint32, tag = "3")]
-    pub depth: u32,
+impl QueryOperator {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            QueryOperator::Or => "Or",
+            QueryOperator::And => "And",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "Or" => Some(Self::Or),
+            "And" => Some(Self::And),
+            _ => None,
+        }
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ReplicationRole {
+    Leader = 0,
+    /// Serves searches from a replica of the index of its leader, and does not crawl.
+    Follower = 1,
+}
+impl ReplicationRole {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            ReplicationRole::Leader => "ReplicationRoleLeader",
+            ReplicationRole::Follower => "ReplicationRoleFollower",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "ReplicationRoleLeader" => Some(Self::Leader),
+            "ReplicationRoleFollower" => Some(Self::Follower),
+            _ => None,
+        }
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum SuggestionSource {
+    Term = 0,
+    Title = 1,
+    Query = 2,
+}
+impl SuggestionSource {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            SuggestionSource::Term => "Term",
+            SuggestionSource::Title => "Title",
+            SuggestionSource::Query => "Query",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "Term" => Some(Self::Term),
+            "Title" => Some(Self::Title),
+            "Query" => Some(Self::Query),
+            _ => None,
+        }
+    }
 }
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
 pub enum ResponseStatus {
     Ok = 0,
     Error = 1,
+    /// Some peers of a federated search failed, the results are those of the others.
+    Partial = 2,
 }
 impl ResponseStatus {
     /// String value of the enum field names used in the ProtoBuf definition.
@@ -55,6 +910,7 @@ impl ResponseStatus {
         match self {
             ResponseStatus::Ok => "Ok",
             ResponseStatus::Error => "Error",
+            ResponseStatus::Partial => "Partial",
         }
     }
     /// Creates an enum from field names used in the ProtoBuf definition.
@@ -62,6 +918,7 @@ impl ResponseStatus {
         match value {
             "Ok" => Some(Self::Ok),
             "Error" => Some(Self::Error),
+            "Partial" => Some(Self::Partial),
             _ => None,
         }
     }
@@ -170,10 +1027,317 @@ pub mod searcher_client {
             req.extensions_mut().insert(GrpcMethod::new("search.Searcher", "Index"));
             self.inner.unary(req, path, codec).await
         }
-        pub async fn search(
+        pub async fn search(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SearchRequest>,
+        ) -> std::result::Result<tonic::Response<super::SearchResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/search.Searcher/Search");
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("search.Searcher", "Search"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn index_with_progress(
+            &mut self,
+            request: impl tonic::IntoRequest<super::IndexRequest>,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::CrawlEvent>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/search.Searcher/IndexWithProgress",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("search.Searcher", "IndexWithProgress"));
+            self.inner.server_streaming(req, path, codec).await
+        }
+        pub async fn suggest(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SuggestRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::SuggestResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/search.Searcher/Suggest");
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("search.Searcher", "Suggest"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn more_like_this(
+            &mut self,
+            request: impl tonic::IntoRequest<super::MoreLikeThisRequest>,
+        ) -> std::result::Result<tonic::Response<super::SearchResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/search.Searcher/MoreLikeThis",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("search.Searcher", "MoreLikeThis"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn get_cache_stats(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CacheStatsRequest>,
+        ) -> std::result::Result<tonic::Response<super::CacheStats>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/search.Searcher/GetCacheStats",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("search.Searcher", "GetCacheStats"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn create_collection(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CreateCollectionRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CollectionResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/search.Searcher/CreateCollection",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("search.Searcher", "CreateCollection"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn list_collections(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListCollectionsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListCollectionsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/search.Searcher/ListCollections",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("search.Searcher", "ListCollections"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Drops a collection along with its index. The default collection cannot be dropped.
+        pub async fn drop_collection(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DropCollectionRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CollectionResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/search.Searcher/DropCollection",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("search.Searcher", "DropCollection"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Streams the operations of the replication log of a collection, for the followers.
+        pub async fn replicate(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ReplicateRequest>,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::ReplicationEvent>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/search.Searcher/Replicate",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("search.Searcher", "Replicate"));
+            self.inner.server_streaming(req, path, codec).await
+        }
+        pub async fn get_replication_status(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ReplicationStatusRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ReplicationStatus>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/search.Searcher/GetReplicationStatus",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("search.Searcher", "GetReplicationStatus"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Streams a point-in-time archive of the index and the replication log of a collection.
+        pub async fn snapshot(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SnapshotRequest>,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::SnapshotChunk>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/search.Searcher/Snapshot");
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("search.Searcher", "Snapshot"));
+            self.inner.server_streaming(req, path, codec).await
+        }
+        /// Loads an archive streamed by `Snapshot` into an empty collection.
+        pub async fn restore(
+            &mut self,
+            request: impl tonic::IntoStreamingRequest<Message = super::SnapshotChunk>,
+        ) -> std::result::Result<
+            tonic::Response<super::RestoreResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/search.Searcher/Restore");
+            let mut req = request.into_streaming_request();
+            req.extensions_mut().insert(GrpcMethod::new("search.Searcher", "Restore"));
+            self.inner.client_streaming(req, path, codec).await
+        }
+        /// Streams every stored page of a collection as JSON Lines, one object of stored fields per
+        /// page.
+        pub async fn export(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ExportRequest>,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::DocumentChunk>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/search.Searcher/Export");
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("search.Searcher", "Export"));
+            self.inner.server_streaming(req, path, codec).await
+        }
+        /// Indexes the pages of JSON Lines streamed as by `Export` into a collection.
+        pub async fn import(
             &mut self,
-            request: impl tonic::IntoRequest<super::SearchRequest>,
-        ) -> std::result::Result<tonic::Response<super::SearchResponse>, tonic::Status> {
+            request: impl tonic::IntoStreamingRequest<Message = super::DocumentChunk>,
+        ) -> std::result::Result<tonic::Response<super::ImportResponse>, tonic::Status> {
             self.inner
                 .ready()
                 .await
@@ -184,10 +1348,10 @@ pub mod searcher_client {
                     )
                 })?;
             let codec = tonic::codec::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static("/search.Searcher/Search");
-            let mut req = request.into_request();
-            req.extensions_mut().insert(GrpcMethod::new("search.Searcher", "Search"));
-            self.inner.unary(req, path, codec).await
+            let path = http::uri::PathAndQuery::from_static("/search.Searcher/Import");
+            let mut req = request.into_streaming_request();
+            req.extensions_mut().insert(GrpcMethod::new("search.Searcher", "Import"));
+            self.inner.client_streaming(req, path, codec).await
         }
     }
 }
@@ -206,6 +1370,104 @@ pub mod searcher_server {
             &self,
             request: tonic::Request<super::SearchRequest>,
         ) -> std::result::Result<tonic::Response<super::SearchResponse>, tonic::Status>;
+        /// Server streaming response type for the IndexWithProgress method.
+        type IndexWithProgressStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<super::CrawlEvent, tonic::Status>,
+            >
+            + Send
+            + 'static;
+        async fn index_with_progress(
+            &self,
+            request: tonic::Request<super::IndexRequest>,
+        ) -> std::result::Result<
+            tonic::Response<Self::IndexWithProgressStream>,
+            tonic::Status,
+        >;
+        async fn suggest(
+            &self,
+            request: tonic::Request<super::SuggestRequest>,
+        ) -> std::result::Result<tonic::Response<super::SuggestResponse>, tonic::Status>;
+        async fn more_like_this(
+            &self,
+            request: tonic::Request<super::MoreLikeThisRequest>,
+        ) -> std::result::Result<tonic::Response<super::SearchResponse>, tonic::Status>;
+        async fn get_cache_stats(
+            &self,
+            request: tonic::Request<super::CacheStatsRequest>,
+        ) -> std::result::Result<tonic::Response<super::CacheStats>, tonic::Status>;
+        async fn create_collection(
+            &self,
+            request: tonic::Request<super::CreateCollectionRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CollectionResponse>,
+            tonic::Status,
+        >;
+        async fn list_collections(
+            &self,
+            request: tonic::Request<super::ListCollectionsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListCollectionsResponse>,
+            tonic::Status,
+        >;
+        /// Drops a collection along with its index. The default collection cannot be dropped.
+        async fn drop_collection(
+            &self,
+            request: tonic::Request<super::DropCollectionRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CollectionResponse>,
+            tonic::Status,
+        >;
+        /// Server streaming response type for the Replicate method.
+        type ReplicateStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<super::ReplicationEvent, tonic::Status>,
+            >
+            + Send
+            + 'static;
+        /// Streams the operations of the replication log of a collection, for the followers.
+        async fn replicate(
+            &self,
+            request: tonic::Request<super::ReplicateRequest>,
+        ) -> std::result::Result<tonic::Response<Self::ReplicateStream>, tonic::Status>;
+        async fn get_replication_status(
+            &self,
+            request: tonic::Request<super::ReplicationStatusRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ReplicationStatus>,
+            tonic::Status,
+        >;
+        /// Server streaming response type for the Snapshot method.
+        type SnapshotStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<super::SnapshotChunk, tonic::Status>,
+            >
+            + Send
+            + 'static;
+        /// Streams a point-in-time archive of the index and the replication log of a collection.
+        async fn snapshot(
+            &self,
+            request: tonic::Request<super::SnapshotRequest>,
+        ) -> std::result::Result<tonic::Response<Self::SnapshotStream>, tonic::Status>;
+        /// Loads an archive streamed by `Snapshot` into an empty collection.
+        async fn restore(
+            &self,
+            request: tonic::Request<tonic::Streaming<super::SnapshotChunk>>,
+        ) -> std::result::Result<tonic::Response<super::RestoreResponse>, tonic::Status>;
+        /// Server streaming response type for the Export method.
+        type ExportStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<super::DocumentChunk, tonic::Status>,
+            >
+            + Send
+            + 'static;
+        /// Streams every stored page of a collection as JSON Lines, one object of stored fields per
+        /// page.
+        async fn export(
+            &self,
+            request: tonic::Request<super::ExportRequest>,
+        ) -> std::result::Result<tonic::Response<Self::ExportStream>, tonic::Status>;
+        /// Indexes the pages of JSON Lines streamed as by `Export` into a collection.
+        async fn import(
+            &self,
+            request: tonic::Request<tonic::Streaming<super::DocumentChunk>>,
+        ) -> std::result::Result<tonic::Response<super::ImportResponse>, tonic::Status>;
     }
     #[derive(Debug)]
     pub struct SearcherServer<T: Searcher> {
@@ -288,21 +1550,340 @@ pub mod searcher_server {
             match req.uri().path() {
                 "/search.Searcher/Index" => {
                     #[allow(non_camel_case_types)]
-                    struct IndexSvc<T: Searcher>(pub Arc<T>);
-                    impl<T: Searcher> tonic::server::UnaryService<super::IndexRequest>
-                    for IndexSvc<T> {
-                        type Response = super::IndexResponse;
+                    struct IndexSvc<T: Searcher>(pub Arc<T>);
+                    impl<T: Searcher> tonic::server::UnaryService<super::IndexRequest>
+                    for IndexSvc<T> {
+                        type Response = super::IndexResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::IndexRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Searcher>::index(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = IndexSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/search.Searcher/Search" => {
+                    #[allow(non_camel_case_types)]
+                    struct SearchSvc<T: Searcher>(pub Arc<T>);
+                    impl<T: Searcher> tonic::server::UnaryService<super::SearchRequest>
+                    for SearchSvc<T> {
+                        type Response = super::SearchResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SearchRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Searcher>::search(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = SearchSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/search.Searcher/IndexWithProgress" => {
+                    #[allow(non_camel_case_types)]
+                    struct IndexWithProgressSvc<T: Searcher>(pub Arc<T>);
+                    impl<
+                        T: Searcher,
+                    > tonic::server::ServerStreamingService<super::IndexRequest>
+                    for IndexWithProgressSvc<T> {
+                        type Response = super::CrawlEvent;
+                        type ResponseStream = T::IndexWithProgressStream;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::IndexRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Searcher>::index_with_progress(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = IndexWithProgressSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/search.Searcher/Suggest" => {
+                    #[allow(non_camel_case_types)]
+                    struct SuggestSvc<T: Searcher>(pub Arc<T>);
+                    impl<T: Searcher> tonic::server::UnaryService<super::SuggestRequest>
+                    for SuggestSvc<T> {
+                        type Response = super::SuggestResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SuggestRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Searcher>::suggest(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = SuggestSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/search.Searcher/MoreLikeThis" => {
+                    #[allow(non_camel_case_types)]
+                    struct MoreLikeThisSvc<T: Searcher>(pub Arc<T>);
+                    impl<
+                        T: Searcher,
+                    > tonic::server::UnaryService<super::MoreLikeThisRequest>
+                    for MoreLikeThisSvc<T> {
+                        type Response = super::SearchResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::MoreLikeThisRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Searcher>::more_like_this(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = MoreLikeThisSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/search.Searcher/GetCacheStats" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetCacheStatsSvc<T: Searcher>(pub Arc<T>);
+                    impl<
+                        T: Searcher,
+                    > tonic::server::UnaryService<super::CacheStatsRequest>
+                    for GetCacheStatsSvc<T> {
+                        type Response = super::CacheStats;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CacheStatsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Searcher>::get_cache_stats(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetCacheStatsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/search.Searcher/CreateCollection" => {
+                    #[allow(non_camel_case_types)]
+                    struct CreateCollectionSvc<T: Searcher>(pub Arc<T>);
+                    impl<
+                        T: Searcher,
+                    > tonic::server::UnaryService<super::CreateCollectionRequest>
+                    for CreateCollectionSvc<T> {
+                        type Response = super::CollectionResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CreateCollectionRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Searcher>::create_collection(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = CreateCollectionSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/search.Searcher/ListCollections" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListCollectionsSvc<T: Searcher>(pub Arc<T>);
+                    impl<
+                        T: Searcher,
+                    > tonic::server::UnaryService<super::ListCollectionsRequest>
+                    for ListCollectionsSvc<T> {
+                        type Response = super::ListCollectionsResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::IndexRequest>,
+                            request: tonic::Request<super::ListCollectionsRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as Searcher>::index(&inner, request).await
+                                <T as Searcher>::list_collections(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -314,7 +1895,7 @@ pub mod searcher_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = IndexSvc(inner);
+                        let method = ListCollectionsSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -330,23 +1911,25 @@ pub mod searcher_server {
                     };
                     Box::pin(fut)
                 }
-                "/search.Searcher/Search" => {
+                "/search.Searcher/DropCollection" => {
                     #[allow(non_camel_case_types)]
-                    struct SearchSvc<T: Searcher>(pub Arc<T>);
-                    impl<T: Searcher> tonic::server::UnaryService<super::SearchRequest>
-                    for SearchSvc<T> {
-                        type Response = super::SearchResponse;
+                    struct DropCollectionSvc<T: Searcher>(pub Arc<T>);
+                    impl<
+                        T: Searcher,
+                    > tonic::server::UnaryService<super::DropCollectionRequest>
+                    for DropCollectionSvc<T> {
+                        type Response = super::CollectionResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::SearchRequest>,
+                            request: tonic::Request<super::DropCollectionRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as Searcher>::search(&inner, request).await
+                                <T as Searcher>::drop_collection(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -358,7 +1941,101 @@ pub mod searcher_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = SearchSvc(inner);
+                        let method = DropCollectionSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/search.Searcher/Replicate" => {
+                    #[allow(non_camel_case_types)]
+                    struct ReplicateSvc<T: Searcher>(pub Arc<T>);
+                    impl<
+                        T: Searcher,
+                    > tonic::server::ServerStreamingService<super::ReplicateRequest>
+                    for ReplicateSvc<T> {
+                        type Response = super::ReplicationEvent;
+                        type ResponseStream = T::ReplicateStream;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ReplicateRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Searcher>::replicate(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ReplicateSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/search.Searcher/GetReplicationStatus" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetReplicationStatusSvc<T: Searcher>(pub Arc<T>);
+                    impl<
+                        T: Searcher,
+                    > tonic::server::UnaryService<super::ReplicationStatusRequest>
+                    for GetReplicationStatusSvc<T> {
+                        type Response = super::ReplicationStatus;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ReplicationStatusRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Searcher>::get_replication_status(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetReplicationStatusSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -374,6 +2051,196 @@ pub mod searcher_server {
                     };
                     Box::pin(fut)
                 }
+                "/search.Searcher/Snapshot" => {
+                    #[allow(non_camel_case_types)]
+                    struct SnapshotSvc<T: Searcher>(pub Arc<T>);
+                    impl<
+                        T: Searcher,
+                    > tonic::server::ServerStreamingService<super::SnapshotRequest>
+                    for SnapshotSvc<T> {
+                        type Response = super::SnapshotChunk;
+                        type ResponseStream = T::SnapshotStream;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SnapshotRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Searcher>::snapshot(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = SnapshotSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/search.Searcher/Restore" => {
+                    #[allow(non_camel_case_types)]
+                    struct RestoreSvc<T: Searcher>(pub Arc<T>);
+                    impl<
+                        T: Searcher,
+                    > tonic::server::ClientStreamingService<super::SnapshotChunk>
+                    for RestoreSvc<T> {
+                        type Response = super::RestoreResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<
+                                tonic::Streaming<super::SnapshotChunk>,
+                            >,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Searcher>::restore(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = RestoreSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.client_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/search.Searcher/Export" => {
+                    #[allow(non_camel_case_types)]
+                    struct ExportSvc<T: Searcher>(pub Arc<T>);
+                    impl<
+                        T: Searcher,
+                    > tonic::server::ServerStreamingService<super::ExportRequest>
+                    for ExportSvc<T> {
+                        type Response = super::DocumentChunk;
+                        type ResponseStream = T::ExportStream;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ExportRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Searcher>::export(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ExportSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/search.Searcher/Import" => {
+                    #[allow(non_camel_case_types)]
+                    struct ImportSvc<T: Searcher>(pub Arc<T>);
+                    impl<
+                        T: Searcher,
+                    > tonic::server::ClientStreamingService<super::DocumentChunk>
+                    for ImportSvc<T> {
+                        type Response = super::ImportResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<
+                                tonic::Streaming<super::DocumentChunk>,
+                            >,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Searcher>::import(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ImportSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.client_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         Ok(
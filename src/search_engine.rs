@@ -1,30 +1,148 @@
-use std::sync::Mutex;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::{BufRead, Read, Write};
+use std::ops::Bound;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use tantivy::{doc, Index, IndexReader, IndexWriter, ReloadPolicy};
-use tantivy::collector::TopDocs;
-use tantivy::query::QueryParser;
+use reqwest::Url;
+use tantivy::{doc, DateTime, DocAddress, DocId, IndexWriter, Score, SegmentReader, Term};
+use tantivy::collector::{Count, FacetCollector, TopDocs};
+use tantivy::query::{BooleanQuery, BoostQuery, ConstScoreQuery, FuzzyTermQuery, MoreLikeThisQuery, Occur, Query, QueryParser, RangeQuery, TermQuery};
+use tantivy::tokenizer::TokenStream;
 use tantivy::schema::*;
 use tempfile::TempDir;
 
-use crate::search::SearchResult;
+use crate::cache::{CacheConfig, ResultCache};
+use crate::collapse;
+use crate::embedding::{Embedder, HashingEmbedder};
+use crate::expression::Expression;
+use crate::fusion::FusionConfig;
+use crate::hash::fnv1a;
+use crate::hnsw::Hnsw;
+use crate::language;
+use crate::link_graph::LinkGraph;
+use crate::passage::{PassageConfig, PassageIndex};
+use crate::ranking::RankingConfig;
+use crate::replication::{self, ReplicationLog};
+use crate::shard::{self, Shard, ShardConfig, ShardedSearcher};
+use crate::snapshot::{self, Manifest};
+use crate::sort::sort_value_reader;
+use crate::spelling;
+use crate::suggest::{self, QueryLog};
+use crate::search::log_entry::Operation;
+use crate::search::more_like_this_request::Like;
+use crate::search::{AnalyzeLinks, CacheStats, FacetBucket, LogEntry, FacetKind, FacetResult, MoreLikeThisRequest, Passage, QueryOperator, ResponseStatus, Retriever, SearchFilters, SearchMode, SearchRequest, SearchResponse, SearchResult, Suggestion, SuggestionSource};
+
+// Replication log, within the index directory.
+const LOG_FILE: &str = "replication.log";
+// Authority given to pages until the link graph has been analyzed.
+const DEFAULT_AUTHORITY: f64 = 1.0;
+// Relative change of authority below which link analysis keeps the stored authority of a page.
+// Authority is rescaled to a mean of 1, so every new page shifts the authority of all others.
+const AUTHORITY_TOLERANCE: f64 = 0.05;
+// Searches with fewer hits than this get a spelling suggestion.
+const SUGGESTION_THRESHOLD: u64 = 3;
+// Number of results returned unless the request asks otherwise.
+const DEFAULT_LIMIT: u32 = 10;
+// Deepest result a search may return, as collectors allocate room for all results up to it.
+const MAX_RESULT_WINDOW: usize = 10_000;
+// Number of top hits grouped when collapsing results. Hidden hits are only counted among them.
+const MAX_COLLAPSE_CANDIDATES: usize = 1_000;
+// Number of candidates retrieved by each retriever of semantic and hybrid searches, before
+// filtering.
+const RETRIEVER_CANDIDATES: usize = 100;
+// Number of buckets returned per facet unless the request asks otherwise.
+pub(crate) const DEFAULT_FACET_SIZE: u32 = 10;
+// Largest number of buckets returned per facet, as counting allocates room for all of them.
+const MAX_FACET_SIZE: usize = 1_000;
+// Pages imported per commit.
+const IMPORT_BATCH_SIZE: usize = 1_000;
+// Fields searched by default, with the boost of their matches. Language-specific body fields
+// share the boost of `body`.
+const DEFAULT_FIELD_BOOSTS: [(&str, Score); 6] = [
+    ("body", 1.0),
+    ("title", 3.0),
+    ("headings", 2.0),
+    ("description", 1.5),
+    ("url", 1.5),
+    ("anchor_text", 2.0),
+];
+
+/// A hyperlink found on a crawled page.
+pub struct Link {
+    // Absolute URL of the link target.
+    pub url: String,
+    // Text of the `<a>` element.
+    pub anchor_text: String,
+}
+
+/// A crawled page, as handed by the crawler to a `Writer`.
+pub struct Page {
+    pub url: String,
+    pub origin_url: String,
+    pub depth: u32,
+    pub body: String,
+    // Visible text of the body, without markup, scripts and styles.
+    pub text: String,
+    pub title: String,
+    // Meta description of the page.
+    pub description: String,
+    // Text of the `<h1>` to `<h6>` elements.
+    pub headings: Vec<String>,
+    // ISO 639-1 code of the language of the page, empty if unknown.
+    pub language: String,
+    // MIME type of the page, without parameters.
+    pub content_type: String,
+    pub fetched_at: SystemTime,
+    pub links: Vec<Link>,
+}
 
 pub trait Writer {
-    fn write(&self, text: &str, url: &str, origin_url: &str, depth: u32);
+    fn write(&self, page: &Page) -> Result<(), String>;
 }
 
 pub trait Reader {
-    fn read(&self, query: &str) -> Result<Vec<SearchResult>, String>;
+    fn read(&self, request: &SearchRequest) -> Result<SearchResponse, String>;
+    fn suggest(&self, prefix: &str, limit: usize) -> Result<Vec<Suggestion>, String>;
+    fn more_like_this(&self, request: &MoreLikeThisRequest) -> Result<SearchResponse, String>;
+    fn cache_stats(&self) -> CacheStats;
+}
+
+/// Stored field each facet kind counts the values of.
+fn facet_source_field(kind: FacetKind) -> &'static str {
+    match kind {
+        FacetKind::Host => "host",
+        FacetKind::Origin => "origin_url",
+        FacetKind::Language => "language",
+        FacetKind::ContentType => "content_type",
+    }
+}
+
+/// Root of the facet paths of a facet kind, such as `/host`.
+fn facet_root(kind: FacetKind) -> Facet {
+    Facet::from_path([facet_source_field(kind)])
 }
 
 pub struct SearchEngine {
-    // Needed to prevent its destructor from removing the folder
+    // Holds the shards and the replication log, and removes them when dropped.
     index_path: TempDir,
-    index: Index,
-    // Wrapping it with a mutex allows IndexWriter to be mutable and used cross-thread.
-    // The underlying implementation is thread-safe, but cargo doesn't know that
-    index_writer: Mutex<IndexWriter>,
+    // Pages are split across the shards by the hash of their URL.
+    shards: Vec<Shard>,
     schema: Schema,
-    reader: IndexReader
+    query_log: QueryLog,
+    ranking: RankingConfig,
+    embedder: Box<dyn Embedder + Send + Sync>,
+    // Embeddings of the pages, by URL.
+    vectors: RwLock<Hnsw>,
+    // Passages of the pages, unless disabled.
+    passages: Option<PassageIndex>,
+    cache: ResultCache,
+    // Operations changing the index, streamed to the followers.
+    log: Arc<ReplicationLog>,
+    // Held by writes, and exclusively by snapshots to pause them.
+    writes: RwLock<()>
 }
 
 unsafe impl Send for SearchEngine {}
@@ -32,50 +150,820 @@ unsafe impl Sync for SearchEngine {}
 
 impl Default for SearchEngine {
     fn default() -> Self {
+        Self::new(RankingConfig::default(), PassageConfig::default(), CacheConfig::default(), ShardConfig::default())
+    }
+}
+
+impl SearchEngine {
+    /// Creates an empty search engine ranking results with the given weights by default,
+    /// splitting pages into passages of the given size, caching results within the given memory
+    /// bound and splitting the index into the given number of shards.
+    pub fn new(ranking: RankingConfig, passages: PassageConfig, cache: CacheConfig, shards: ShardConfig) -> Self {
         let index_path = TempDir::new().expect("Unable to create temp dir");
+        let schema = Self::schema();
+        let shards = (0..shards.shards)
+            .map(|shard| Shard::create_in_dir(&shard_path(index_path.path(), shard), schema.clone()))
+            .collect();
+        let log = ReplicationLog::create(&index_path.path().join(LOG_FILE));
+        Self::with_parts(index_path, schema, shards, log, ranking, passages, cache)
+    }
+
+    /// Restores the index and the replication log of a snapshot archive into a new search engine.
+    /// Embeddings and passages are not archived, but recomputed from the stored pages.
+    pub fn restore(archive: impl Read, ranking: RankingConfig, passages: PassageConfig, cache: CacheConfig) -> Result<(Self, Manifest), String> {
+        let index_path = TempDir::new().map_err(|e| e.to_string())?;
+        let manifest = snapshot::extract_archive(archive, index_path.path())?;
+        if manifest.shards == 0 {
+            return Err("Snapshot has no shards".to_string());
+        }
+        let shards = (0..manifest.shards)
+            .map(|shard| Shard::open_in_dir(&shard_path(index_path.path(), shard)))
+            .collect::<Result<Vec<_>, String>>()?;
+        let schema = Self::schema();
+        if shards.iter().any(|shard| shard.index.schema() != schema) {
+            return Err("Snapshot schema differs from the schema of this server".to_string());
+        }
+        let log = ReplicationLog::open(&index_path.path().join(LOG_FILE), manifest.sequence)?;
+        let engine = Self::with_parts(index_path, schema, shards, log, ranking, passages, cache);
+        let [url_field, title_field, description_field, text_field] = ["url", "title", "description", "text"]
+            .map(|name| engine.schema.get_field(name).unwrap());
+        let mut vectors = engine.vectors.write().unwrap();
+        engine.for_each_stored(|document| {
+            let url = get_text_field_value(&document, url_field);
+            let text = get_text_field_value(&document, text_field);
+            let title = get_text_field_value(&document, title_field);
+            let description = get_text_field_value(&document, description_field);
+            vectors.insert(&url, engine.embed(&title, &description, &text));
+            match &engine.passages {
+                Some(passages) => passages.add(&url, &text),
+                None => Ok(())
+            }
+        })?;
+        drop(vectors);
+        if let Some(passages) = &engine.passages {
+            passages.commit()?;
+        }
+        Ok((engine, manifest))
+    }
+
+    /// Writes a snapshot archive of the index and the replication log. Writes are paused while the
+    /// committed files are collected, but not while the archive is written. The log is then
+    /// truncated up to `RETAINED_OPERATIONS` before the snapshot.
+    pub fn snapshot(&self, out: impl Write) -> Result<Manifest, String> {
+        let writes = self.writes.write().unwrap();
+        let mut files = Vec::new();
+        for (shard, Shard { index, .. }) in self.shards.iter().enumerate() {
+            for (path, size, file) in snapshot::committed_files(index, &shard_path(self.index_path.path(), shard))? {
+                files.push((Path::new(&shard_dir(shard)).join(path), size, file));
+            }
+        }
+        let (log, log_size) = self.log.committed()?;
+        let sequence = self.log.sequence();
+        drop(writes);
+        files.push((PathBuf::from(LOG_FILE), log_size, Box::new(log.take(log_size))));
+        let created_at = SystemTime::now().duration_since(UNIX_EPOCH).map_err(|e| e.to_string())?.as_secs() as i64;
+        let manifest = Manifest::new(self.shards.len(), sequence, created_at);
+        snapshot::write_archive(&manifest, files, out)?;
+        self.log.truncate(sequence.saturating_sub(replication::RETAINED_OPERATIONS))?;
+        Ok(manifest)
+    }
+
+    /// Whether nothing was ever written to the search engine.
+    pub fn is_empty(&self) -> bool {
+        self.log.sequence() == 0
+    }
+
+    /// Writes every stored page, with all its stored fields, as a JSON object per line. Returns the
+    /// number of pages written.
+    pub fn export(&self, mut out: impl Write) -> Result<u64, String> {
+        self.reload()?;
+        let mut pages = 0;
+        self.for_each_stored(|document| {
+            writeln!(out, "{}", self.schema.to_json(&document)).map_err(|e| format!("Failed to export. Error: {}", e))?;
+            pages += 1;
+            Ok(())
+        })?;
+        out.flush().map_err(|e| format!("Failed to export. Error: {}", e))?;
+        Ok(pages)
+    }
+
+    /// Indexes the pages of an export as crawled pages are, committing them in batches. The fields
+    /// derived from the link graph are recomputed rather than imported. Returns the number of pages
+    /// imported.
+    pub fn import(&self, input: impl BufRead) -> Result<u64, String> {
+        let mut pages = 0;
+        let mut batch = Vec::new();
+        let mut flush = |batch: &mut Vec<Page>| {
+            let written = self.write_pages(batch);
+            if written.is_ok() {
+                pages += batch.len() as u64;
+            }
+            batch.clear();
+            written
+        };
+        let read = || {
+            for (number, line) in input.lines().enumerate() {
+                let line = line.map_err(|e| format!("Failed to import line {}. Error: {}", number + 1, e))?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let page = self.schema.parse_document(&line)
+                    .map_err(|e| e.to_string())
+                    .and_then(|document| self.stored_page(&document))
+                    .map_err(|e| format!("Failed to import line {}. Error: {}", number + 1, e))?;
+                batch.push(page);
+                if batch.len() == IMPORT_BATCH_SIZE {
+                    flush(&mut batch)?;
+                }
+            }
+            Ok(())
+        };
+        // The pages read before a failing line are imported all the same, and their link signals
+        // computed.
+        let imported = read().and(flush(&mut batch));
+        if pages > 0 {
+            self.analyze_links()?;
+        }
+        imported.map(|()| pages)
+    }
+
+    /// The document indexing the page, with the default authority until links are analyzed.
+    fn page_document(&self, page: &Page) -> Result<Document, String> {
+        let url_field = self.schema.get_field("url").unwrap();
+        let origin_url_field = self.schema.get_field("origin_url").unwrap();
+        let depth_field = self.schema.get_field("depth").unwrap();
+        let body_field = self.schema.get_field("body").unwrap();
+        let text_field = self.schema.get_field("text").unwrap();
+        let title_field = self.schema.get_field("title").unwrap();
+        let description_field = self.schema.get_field("description").unwrap();
+        let headings_field = self.schema.get_field("headings").unwrap();
+        let outlinks_field = self.schema.get_field("outlinks").unwrap();
+        let outlink_anchors_field = self.schema.get_field("outlink_anchors").unwrap();
+        let authority_field = self.schema.get_field("authority").unwrap();
+        let language_field = self.schema.get_field("language").unwrap();
+        let host_field = self.schema.get_field("host").unwrap();
+        let content_type_field = self.schema.get_field("content_type").unwrap();
+        let fetched_at_field = self.schema.get_field("fetched_at").unwrap();
+        let host = Url::parse(&page.url).ok()
+            .and_then(|url| url.host_str().map(|host| host.to_string()))
+            .unwrap_or_default();
+        let fetched_at = page.fetched_at.duration_since(UNIX_EPOCH).map_err(|e| e.to_string())?;
+        let mut document = doc!(
+        url_field => page.url.as_str(),
+        origin_url_field => page.origin_url.as_str(),
+        host_field => host,
+        depth_field => page.depth as u64,
+        content_type_field => page.content_type.as_str(),
+        fetched_at_field => DateTime::from_timestamp_secs(fetched_at.as_secs() as i64),
+        body_field => page.body.as_str(),
+        text_field => page.text.as_str(),
+        title_field => page.title.as_str(),
+        description_field => page.description.as_str(),
+        authority_field => DEFAULT_AUTHORITY,
+        language_field => page.language.as_str()
+        );
+        for heading in &page.headings {
+            document.add_text(headings_field, heading);
+        }
+        for link in &page.links {
+            document.add_text(outlinks_field, &link.url);
+            document.add_text(outlink_anchors_field, &link.anchor_text);
+        }
+        self.add_derived_fields(&mut document);
+        Ok(document)
+    }
+
+    /// Indexes the pages, which replace their previous versions, with one commit per shard written
+    /// to and one commit of the passages.
+    fn write_pages(&self, pages: &[Page]) -> Result<(), String> {
+        let url_key_field = self.schema.get_field("url_key").unwrap();
+        if pages.is_empty() {
+            return Ok(());
+        }
+        let documents = pages.iter().map(|page| self.page_document(page)).collect::<Result<Vec<_>, _>>()?;
+        let embeddings: Vec<_> = pages.iter().map(|page| self.embed(&page.title, &page.description, &page.text)).collect();
+        let _writes = self.writes.read().unwrap();
+        // Writes to distinct shards run in parallel, each shard having its own writer. Writers are
+        // locked in the order of their shards.
+        let mut guards: BTreeMap<usize, _> = pages.iter()
+            .map(|page| shard::shard_of(&page.url, self.shards.len()))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|shard| (shard, self.shards[shard].index_writer.lock().unwrap()))
+            .collect();
+        for (page, document) in pages.iter().zip(documents) {
+            let guard = guards.get_mut(&shard::shard_of(&page.url, self.shards.len())).unwrap();
+            // Re-crawled pages replace their previous version.
+            guard.delete_term(Term::from_field_text(url_key_field, &page.url));
+            guard.add_document(document).map_err(|e| format!("Failed to index {}. Error: {}", page.url, e))?;
+        }
+        for guard in guards.values_mut() {
+            guard.commit().map_err(|e| format!("Failed to index {} pages. Error: {}", pages.len(), e))?;
+        }
+        for page in pages {
+            self.log.append(Operation::Put(page.into()))?;
+        }
+        drop(guards);
+        let mut vectors = self.vectors.write().unwrap();
+        for (page, embedding) in pages.iter().zip(embeddings) {
+            vectors.insert(&page.url, embedding);
+        }
+        drop(vectors);
+        if let Some(passages) = &self.passages {
+            for page in pages {
+                passages.add(&page.url, &page.text)?;
+            }
+            passages.commit()?;
+        }
+        // The reader may have reloaded before the embeddings and passages were added.
+        self.cache.clear();
+        Ok(())
+    }
+
+    /// The page a stored document was written from.
+    fn stored_page(&self, document: &Document) -> Result<Page, String> {
+        let field = |name: &str| self.schema.get_field(name).unwrap();
+        let text = |name: &str| document.get_first(field(name)).and_then(|value| value.as_text()).unwrap_or_default().to_string();
+        let texts = |name: &str| document.get_all(field(name)).filter_map(|value| value.as_text()).map(str::to_string).collect::<Vec<_>>();
+        let url = text("url");
+        if url.is_empty() {
+            return Err("The page has no url".to_string());
+        }
+        let fetched_at = document.get_first(field("fetched_at")).and_then(|value| value.as_date())
+            .map_or(0, |date| date.into_timestamp_secs().max(0) as u64);
+        Ok(Page {
+            url,
+            origin_url: text("origin_url"),
+            depth: document.get_first(field("depth")).and_then(|value| value.as_u64()).unwrap_or_default() as u32,
+            body: text("body"),
+            text: text("text"),
+            title: text("title"),
+            description: text("description"),
+            headings: texts("headings"),
+            language: text("language"),
+            content_type: text("content_type"),
+            fetched_at: UNIX_EPOCH + Duration::from_secs(fetched_at),
+            links: texts("outlinks").into_iter().zip(texts("outlink_anchors"))
+                .map(|(url, anchor_text)| Link { url, anchor_text })
+                .collect(),
+        })
+    }
+
+    fn schema() -> Schema {
         let mut schema_builder = Schema::builder();
-        schema_builder.add_text_field("url", STRING | STORED);
-        schema_builder.add_text_field("origin_url", STRING | STORED);
-        schema_builder.add_u64_field("depth", STORED);
-        schema_builder.add_text_field("body", TEXT);
-        let schema = schema_builder.build();
-        let index = Index::create_in_dir(&index_path, schema.clone()).expect("Unable to create index");
-        let index_writer = index.writer(50_000_000).expect("Unable to create writer");
-        let reader = index
-            .reader_builder()
-            .reload_policy(ReloadPolicy::OnCommit)
-            .try_into().expect("Unable to create reader");
+        schema_builder.add_text_field("url", TEXT | STORED);
+        // The untokenized URL, identifying the page's document.
+        schema_builder.add_text_field("url_key", STRING | FAST);
+        schema_builder.add_u64_field("url_length", FAST);
+        schema_builder.add_text_field("origin_url", STRING | STORED | FAST);
+        schema_builder.add_text_field("host", STRING | STORED | FAST);
+        schema_builder.add_u64_field("depth", INDEXED | STORED | FAST);
+        schema_builder.add_text_field("content_type", STRING | STORED | FAST);
+        schema_builder.add_date_field("fetched_at", INDEXED | STORED | FAST);
+        schema_builder.add_text_field("body", TEXT | STORED);
+        // Visible text of the body, embedded and split into passages.
+        schema_builder.add_text_field("text", STORED);
+        // Hash of the body, shared by duplicate pages.
+        schema_builder.add_u64_field("content_hash", FAST);
+        schema_builder.add_text_field("title", TEXT | STORED);
+        // The whole normalized title, completed by query suggestions.
+        schema_builder.add_text_field("title_suggest", STRING);
+        schema_builder.add_text_field("description", TEXT | STORED);
+        schema_builder.add_text_field("headings", TEXT | STORED);
+        schema_builder.add_text_field("outlinks", STRING | STORED);
+        // Anchor texts of the outlinks, in the same order as `outlinks`.
+        schema_builder.add_text_field("outlink_anchors", STORED);
+        // Anchor texts of the inbound links, collected from the pages linking here.
+        schema_builder.add_text_field("anchor_text", TEXT | STORED);
+        schema_builder.add_f64_field("authority", FAST | STORED);
+        // Facets of all kinds, under `/<field>/<value>`.
+        schema_builder.add_facet_field("facets", FacetOptions::default());
+        schema_builder.add_text_field("language", STRING | STORED | FAST);
+        // The body again, analyzed for the page's language.
+        for (name, analyzer) in language::body_fields() {
+            let indexing = TextFieldIndexing::default()
+                .set_tokenizer(&analyzer)
+                .set_index_option(IndexRecordOption::WithFreqsAndPositions);
+            schema_builder.add_text_field(&name, TextOptions::default().set_indexing_options(indexing));
+        }
+        schema_builder.build()
+    }
+
+    fn with_parts(index_path: TempDir, schema: Schema, shards: Vec<Shard>, log: ReplicationLog, ranking: RankingConfig, passages: PassageConfig, cache: CacheConfig) -> Self {
+        let passages = (passages.words > 0)
+            .then(|| PassageIndex::create_in_dir(&index_path.path().join("passages"), passages));
         Self {
             index_path,
-            index,
-            index_writer: Mutex::new(index_writer),
+            shards,
             schema,
-            reader
+            query_log: QueryLog::default(),
+            ranking,
+            embedder: Box::new(HashingEmbedder::default()),
+            vectors: RwLock::new(Hnsw::default()),
+            passages,
+            cache: ResultCache::new(cache),
+            log: Arc::new(log),
+            writes: RwLock::new(())
         }
     }
-}
-impl Writer for SearchEngine {
-    fn write(&self, text: &str, url: &str, origin_url: &str, depth: u32) {
+
+    /// Adds the indexed-only fields that are derived from the stored ones: the URL key and length,
+    /// the suggestable title, the content hash, the body analyzed for the page's language and the
+    /// facets.
+    fn add_derived_fields(&self, document: &mut Document) {
+        let url_field = self.schema.get_field("url").unwrap();
+        let url_key_field = self.schema.get_field("url_key").unwrap();
+        let title_field = self.schema.get_field("title").unwrap();
+        let title_suggest_field = self.schema.get_field("title_suggest").unwrap();
+        let body_field = self.schema.get_field("body").unwrap();
+        let language_field = self.schema.get_field("language").unwrap();
+        let facets_field = self.schema.get_field("facets").unwrap();
+        let url_length_field = self.schema.get_field("url_length").unwrap();
+        let url = get_text_field_value(document, url_field);
+        document.add_u64(url_length_field, url.chars().count() as u64);
+        document.add_text(url_key_field, url);
+        let title = suggest::normalize(&get_text_field_value(document, title_field));
+        if !title.is_empty() {
+            document.add_text(title_suggest_field, title);
+        }
+        let body = get_text_field_value(document, body_field);
+        if !body.trim().is_empty() {
+            document.add_u64(self.schema.get_field("content_hash").unwrap(), collapse::content_hash(&body));
+        }
+        if let Some(name) = language::body_field_name(&get_text_field_value(document, language_field)) {
+            document.add_text(self.schema.get_field(&name).unwrap(), body);
+        }
+        for kind in [FacetKind::Host, FacetKind::Origin, FacetKind::Language, FacetKind::ContentType] {
+            let source = facet_source_field(kind);
+            let value = get_text_field_value(document, self.schema.get_field(source).unwrap());
+            if !value.is_empty() {
+                document.add_facet(facets_field, Facet::from_path([source, value.as_str()]));
+            }
+        }
+    }
+
+    /// Rebuilds the link graph from the stored outlinks of all indexed pages, computes PageRank
+    /// and inbound anchor text over it and re-indexes every page whose link signals changed.
+    pub fn analyze_links(&self) -> Result<(), String> {
+        let url_field = self.schema.get_field("url").unwrap();
+        let outlinks_field = self.schema.get_field("outlinks").unwrap();
+        let outlink_anchors_field = self.schema.get_field("outlink_anchors").unwrap();
+        let anchor_text_field = self.schema.get_field("anchor_text").unwrap();
+        let authority_field = self.schema.get_field("authority").unwrap();
+        let _writes = self.writes.read().unwrap();
+        // Writes wait for the analysis, logged before any of them, so that followers applying the
+        // log in order analyze the same pages as the leader.
+        let mut writers: Vec<_> = self.shards.iter().map(|shard| shard.index_writer.lock().unwrap()).collect();
+        self.reload()?;
+        // Pages are streamed rather than held: only their links are kept, along with the previous
+        // authority and a hash of the previous anchor text of each page.
+        let mut graph = LinkGraph::default();
+        let mut previous: HashMap<String, (Option<f64>, u64)> = HashMap::new();
+        self.for_each_stored(|document| {
+            let url = get_text_field_value(&document, url_field);
+            let targets = document.get_all(outlinks_field).filter_map(|value| value.as_text());
+            let anchors = document.get_all(outlink_anchors_field).filter_map(|value| value.as_text());
+            graph.add(&url, targets.zip(anchors).map(|(target, anchor)| (target.to_string(), anchor.to_string())).collect());
+            let anchor_text = document.get_first(anchor_text_field).and_then(|value| value.as_text()).unwrap_or_default();
+            previous.insert(url, (document.get_first(authority_field).and_then(|value| value.as_f64()), fnv1a(anchor_text.as_bytes())));
+            Ok(())
+        })?;
+        let ranks = graph.page_rank();
+        let anchor_texts: HashMap<String, String> = graph.anchor_texts().into_iter()
+            .map(|(url, anchors)| (url, anchors.join("\n")))
+            .collect();
+        drop(graph);
+        // Pages whose authority or anchor text changed, grouped by shard.
+        let mut changed = vec![Vec::new(); self.shards.len()];
+        for (url, (previous_authority, previous_anchor_text)) in previous {
+            let authority = ranks.get(&url).copied().unwrap_or(DEFAULT_AUTHORITY);
+            let anchor_text = anchor_texts.get(&url).map_or("", String::as_str);
+            if previous_authority.is_some_and(|previous| (previous - authority).abs() <= AUTHORITY_TOLERANCE * previous)
+                && previous_anchor_text == fnv1a(anchor_text.as_bytes()) {
+                continue;
+            }
+            changed[shard::shard_of(&url, self.shards.len())].push((url, authority, anchor_text));
+        }
+        thread::scope(|scope| {
+            let updates: Vec<_> = self.shards.iter().zip(writers.iter_mut().map(|writer| &mut **writer)).zip(changed)
+                .filter(|(_, pages)| !pages.is_empty())
+                .map(|((shard, writer), pages)| scope.spawn(move || self.update_link_fields(shard, writer, &pages)))
+                .collect();
+            updates.into_iter().try_for_each(|update| update.join().expect("Link analysis panicked"))
+        })?;
+        self.log.append(Operation::AnalyzeLinks(AnalyzeLinks {}))?;
+        drop(writers);
+        self.reload()
+    }
+
+    /// Rewrites the pages of the shard with their new authority and anchor text, with the writer
+    /// of the shard.
+    fn update_link_fields(&self, shard: &Shard, writer: &mut IndexWriter, pages: &[(String, f64, &str)]) -> Result<(), String> {
+        let url_key_field = self.schema.get_field("url_key").unwrap();
+        let anchor_text_field = self.schema.get_field("anchor_text").unwrap();
+        let authority_field = self.schema.get_field("authority").unwrap();
+        let searcher = shard.reader.searcher();
+        for (url, authority, anchor_text) in pages {
+            let url_query = TermQuery::new(Term::from_field_text(url_key_field, url), IndexRecordOption::Basic);
+            let top_docs = searcher.search(&url_query, &TopDocs::with_limit(1)).map_err(|e| e.to_string())?;
+            let Some((_, doc_address)) = top_docs.first() else { continue };
+            let document = searcher.doc(*doc_address).map_err(|e| e.to_string())?;
+            let mut updated: Document = document.field_values().iter()
+                .filter(|field_value| field_value.field() != authority_field && field_value.field() != anchor_text_field)
+                .cloned()
+                .collect::<Vec<_>>()
+                .into();
+            updated.add_f64(authority_field, *authority);
+            if !anchor_text.is_empty() {
+                updated.add_text(anchor_text_field, anchor_text);
+            }
+            self.add_derived_fields(&mut updated);
+            writer.delete_term(Term::from_field_text(url_key_field, url));
+            writer.add_document(updated).map_err(|e| e.to_string())?;
+        }
+        writer.commit().map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    pub fn replication_log(&self) -> Arc<ReplicationLog> {
+        self.log.clone()
+    }
+
+    /// Applies an operation of the log of the leader, which must follow the last one applied.
+    pub fn apply(&self, entry: LogEntry) -> Result<(), String> {
+        let expected = self.log.sequence() + 1;
+        if entry.sequence != expected {
+            return Err(format!("Expected operation {} of the replication log, got {}", expected, entry.sequence));
+        }
+        match entry.operation {
+            Some(Operation::Put(page)) => self.write(&page.into()),
+            Some(Operation::AnalyzeLinks(_)) => self.analyze_links(),
+            None => Err(format!("Operation {} of the replication log is empty", entry.sequence))
+        }
+    }
+
+    /// Calls the function with the stored fields of every page.
+    fn for_each_stored(&self, mut f: impl FnMut(Document) -> Result<(), String>) -> Result<(), String> {
+        let searcher = ShardedSearcher::new(&self.shards);
+        for segment_reader in searcher.segment_readers() {
+            let store = segment_reader.get_store_reader(1).map_err(|e| e.to_string())?;
+            for doc in store.iter(segment_reader.alive_bitset()) {
+                f(doc.map_err(|e| e.to_string())?)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Embeds the title, description and visible text of a page.
+    fn embed(&self, title: &str, description: &str, text: &str) -> Vec<f32> {
+        self.embedder.embed(&format!("{} {} {}", title, description, text))
+    }
+
+    /// Reloads the readers of all shards, so that searches see every commit.
+    fn reload(&self) -> Result<(), String> {
+        self.shards.iter().try_for_each(|shard| shard.reader.reload()).map_err(|e| e.to_string())
+    }
+
+    /// Extracts the terms of a query, ignoring field names and operators, and normalizes them the
+    /// way `body` is analyzed.
+    fn query_terms(&self, query: &str) -> Vec<String> {
+        let body_field = self.schema.get_field("body").unwrap();
+        // All shards share the schema and the analyzers.
+        let mut analyzer = self.shards[0].index.tokenizer_for_field(body_field).unwrap();
+        let mut terms = Vec::new();
+        for word in query.split_whitespace().filter(|word| !matches!(*word, "AND" | "OR" | "NOT")) {
+            let word = word.rsplit(':').next().unwrap_or(word);
+            let mut stream = analyzer.token_stream(word);
+            while stream.advance() {
+                terms.push(stream.token().text.clone());
+            }
+        }
+        terms
+    }
+
+    /// Matches each term with an edit distance scaled to its length in all default fields.
+    fn fuzzy_query(&self, terms: &[String], boosts: &HashMap<String, Score>, operator: QueryOperator) -> Box<dyn Query> {
+        let occur = if operator == QueryOperator::And { Occur::Must } else { Occur::Should };
+        Box::new(BooleanQuery::new(terms.iter().map(|term| {
+            let alternatives: Vec<(Occur, Box<dyn Query>)> = DEFAULT_FIELD_BOOSTS.iter().map(|(name, _)| {
+                let field = self.schema.get_field(name).unwrap();
+                let fuzzy = FuzzyTermQuery::new(Term::from_field_text(field, term), spelling::max_edits(term), true);
+                (Occur::Should, Box::new(BoostQuery::new(Box::new(fuzzy), boosts[*name])) as Box<dyn Query>)
+            }).collect();
+            (occur, Box::new(BooleanQuery::new(alternatives)) as Box<dyn Query>)
+        }).collect()))
+    }
+
+    /// Translates the language and the structured filters of a request into non-scoring clauses.
+    fn filter_clauses(&self, request: &SearchRequest) -> Vec<(Occur, Box<dyn Query>)> {
+        let term = |name: &str, value: &str| -> Box<dyn Query> {
+            let field = self.schema.get_field(name).unwrap();
+            Box::new(TermQuery::new(Term::from_field_text(field, value), IndexRecordOption::Basic))
+        };
+        let mut filters: Vec<Box<dyn Query>> = Vec::new();
+        if let Some(language) = &request.language {
+            filters.push(term("language", language));
+        }
+        let SearchFilters { origin_url, host, min_depth, max_depth, content_type, fetched_after, fetched_before } =
+            request.filters.clone().unwrap_or_default();
+        if let Some(origin_url) = origin_url {
+            filters.push(term("origin_url", &origin_url));
+        }
+        if let Some(host) = host {
+            filters.push(term("host", &host));
+        }
+        if let Some(content_type) = content_type {
+            filters.push(term("content_type", &content_type));
+        }
+        if min_depth.is_some() || max_depth.is_some() {
+            filters.push(Box::new(RangeQuery::new_u64_bounds(
+                "depth".to_string(),
+                min_depth.map_or(Bound::Unbounded, |depth| Bound::Included(depth as u64)),
+                max_depth.map_or(Bound::Unbounded, |depth| Bound::Included(depth as u64))
+            )));
+        }
+        if fetched_after.is_some() || fetched_before.is_some() {
+            filters.push(Box::new(RangeQuery::new_date_bounds(
+                "fetched_at".to_string(),
+                fetched_after.map_or(Bound::Unbounded, |secs| Bound::Included(DateTime::from_timestamp_secs(secs))),
+                fetched_before.map_or(Bound::Unbounded, |secs| Bound::Excluded(DateTime::from_timestamp_secs(secs)))
+            )));
+        }
+        filters.into_iter()
+            .map(|filter| -> (Occur, Box<dyn Query>) { (Occur::Must, Box::new(ConstScoreQuery::new(filter, 0.0))) })
+            .collect()
+    }
+
+    /// Parses the query of a keyword search into a query over the boosted search fields, and
+    /// returns it along with the terms of the query.
+    fn keyword_query(&self, request: &SearchRequest) -> Result<(Box<dyn Query>, Vec<String>), String> {
+        let mut boosts = field_boosts(&request.field_boosts)?;
+        // The query is analyzed for the requested language, or for all of them.
+        let language_fields: Vec<String> = match request.language.as_deref() {
+            Some(language) => language::body_field_name(language).into_iter().collect(),
+            None => language::body_fields().into_iter().map(|(name, _)| name).collect()
+        };
+        let body_boost = boosts["body"];
+        boosts.extend(language_fields.into_iter().map(|name| (name, body_boost)));
+        let fields: Vec<(Field, Score)> = boosts.iter()
+            .map(|(name, boost)| (self.schema.get_field(name).unwrap(), *boost))
+            .collect();
+        let mut query_parser = QueryParser::for_index(&self.shards[0].index, fields.iter().map(|(field, _)| *field).collect());
+        for (field, boost) in fields {
+            query_parser.set_field_boost(field, boost);
+        }
+        if request.operator() == QueryOperator::And {
+            query_parser.set_conjunction_by_default();
+        }
+        let query = match query_parser.parse_query(&request.query) {
+            Ok(r) => Ok(r),
+            Err(e) => Err(e.to_string())
+        }?;
+        let terms = self.query_terms(&request.query);
+        let query = if request.fuzzy {
+            Box::new(BooleanQuery::new(vec![
+                (Occur::Should, query),
+                (Occur::Should, self.fuzzy_query(&terms, &boosts, request.operator()))
+            ]))
+        } else {
+            query
+        };
+        Ok((query, terms))
+    }
+
+    /// URLs of the pages whose embedding is among the nearest neighbours of the query's, with
+    /// their similarity to it, most similar first. Unrelated pages, with no positive similarity,
+    /// are left out.
+    fn semantic_candidates(&self, query: &str, candidates: usize) -> Vec<(String, Score)> {
+        let neighbours = self.vectors.read().unwrap().search(&self.embedder.embed(query), candidates);
+        neighbours.into_iter().filter(|(_, similarity)| *similarity > 0.0).collect()
+    }
+
+    /// URLs of the best `candidates` pages matching the keyword query and the filters of the
+    /// request, with their BM25 score, best first.
+    fn keyword_candidates(&self, searcher: &ShardedSearcher, request: &SearchRequest, candidates: usize) -> Result<Vec<(String, Score)>, String> {
+        let url_field = self.schema.get_field("url").unwrap();
+        let (query, _) = self.keyword_query(request)?;
+        let mut clauses = vec![(Occur::Must, query)];
+        clauses.extend(self.filter_clauses(request));
+        let top_docs = searcher.search(&BooleanQuery::new(clauses), &TopDocs::with_limit(candidates)).map_err(|e| e.to_string())?;
+        Ok(top_docs.into_iter()
+            .filter_map(|(score, doc_address)| searcher.doc(doc_address).ok()
+                .map(|retrieved| (get_text_field_value(&retrieved, url_field), score)))
+            .collect())
+    }
+
+    /// Matches the pages with the given URLs, scored with the given scores.
+    fn scored_urls_query(&self, scores: impl IntoIterator<Item = (String, Score)>) -> Box<dyn Query> {
+        let url_key_field = self.schema.get_field("url_key").unwrap();
+        Box::new(BooleanQuery::new(scores.into_iter()
+            .map(|(url, score)| {
+                let url_query = TermQuery::new(Term::from_field_text(url_key_field, &url), IndexRecordOption::Basic);
+                (Occur::Should, Box::new(ConstScoreQuery::new(Box::new(url_query), score)) as Box<dyn Query>)
+            })
+            .collect()))
+    }
+
+    /// Runs the query restricted by the language and filters of the request, then ranks, sorts,
+    /// counts facets, collapses and paginates the hits as the request asks. The response has no
+    /// spelling suggestion.
+    fn search(&self, searcher: &ShardedSearcher, query: Box<dyn Query>, request: &SearchRequest) -> Result<SearchResponse, String> {
         let url_field = self.schema.get_field("url").unwrap();
         let origin_url_field = self.schema.get_field("origin_url").unwrap();
         let depth_field = self.schema.get_field("depth").unwrap();
-        let body_field = self.schema.get_field("body").unwrap();
-        let mut guard = self.index_writer.lock().unwrap();
-        match guard.add_document(doc!(
-        url_field => url,
-        origin_url_field => origin_url,
-        depth_field => depth as u64,
-        body_field => text
-        )) {
-                Ok(_) => {
-                    match guard.commit() {
-                        Ok(_) => {},
-                        Err(e) => println!("Failed to index {}. Error: {}", url, e)
+        let title_field = self.schema.get_field("title").unwrap();
+        let language_field = self.schema.get_field("language").unwrap();
+        let content_type_field = self.schema.get_field("content_type").unwrap();
+        let fetched_at_field = self.schema.get_field("fetched_at").unwrap();
+        let mut clauses = vec![(Occur::Must, query)];
+        clauses.extend(self.filter_clauses(request));
+        let query = BooleanQuery::new(clauses);
+        let ranking = match &request.ranking {
+            Some(overrides) => self.ranking.with_overrides(overrides)?,
+            None => self.ranking.clone()
+        };
+        let expression = request.score_expression.as_deref().map(Expression::parse).transpose()?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map_err(|e| e.to_string())?.as_secs() as i64;
+        let (sort_by, then_by) = (request.sort_by(), request.then_by());
+        let (offset, limit) = result_window(request.offset, request.limit)?;
+        let facet_size = request.facet_size.unwrap_or(DEFAULT_FACET_SIZE) as usize;
+        if facet_size > MAX_FACET_SIZE {
+            return Err(format!("Facet size must be at most {}", MAX_FACET_SIZE));
+        }
+        // Collapsing needs the hits of the previous pages to know which groups they filled.
+        let candidates = match request.collapse {
+            Some(_) => MAX_COLLAPSE_CANDIDATES.max(offset + limit),
+            None => offset + limit
+        };
+        let collector = TopDocs::with_limit(candidates).tweak_score(move |segment_reader: &SegmentReader| {
+            let scorer = ranking.scorer(segment_reader, now);
+            let expression = expression.as_ref().map(|expression| expression.scorer(segment_reader, now));
+            let primary = sort_value_reader(sort_by, segment_reader);
+            let secondary = sort_value_reader(then_by, segment_reader);
+            move |doc: DocId, score: Score| {
+                let score = scorer(doc, score);
+                let score = expression.as_ref().map_or(score, |expression| expression(doc, score));
+                (primary(doc, score), secondary(doc, score), score)
+            }
+        });
+        let mut facet_collector = FacetCollector::for_field("facets");
+        for kind in request.facets() {
+            facet_collector.add_facet(facet_root(kind));
+        }
+        let (top_docs, facet_counts, total_hits) = match searcher.search(&query, &(collector, facet_collector, Count)) {
+            Ok(r) => Ok(r),
+            Err(e) => Err(e.to_string())
+        }?;
+        let facets = request.facets().map(|kind| FacetResult {
+            kind: kind.into(),
+            buckets: facet_counts.top_k(facet_root(kind), facet_size).into_iter()
+                .map(|(facet, count)| FacetBucket {
+                    value: facet.to_path().last().map(|value| value.to_string()).unwrap_or_default(),
+                    count
+                })
+                .collect()
+        }).collect();
+        let top_docs: Vec<_> = top_docs.into_iter().map(|((_, _, score), doc_address)| (score, doc_address)).collect();
+        let top_docs: Vec<((Score, DocAddress), u64)> = match &request.collapse {
+            Some(collapse) => {
+                let group_readers: Vec<_> = searcher.segment_readers().iter()
+                    .map(|segment_reader| collapse::group_reader(collapse.by(), segment_reader))
+                    .collect();
+                let ranked = top_docs.into_iter()
+                    .map(|(score, doc_address)| (group_readers[doc_address.segment_ord as usize](doc_address.doc_id), (score, doc_address)))
+                    .collect();
+                collapse::collapse(ranked, collapse.max_per_group.max(1) as usize, offset + limit)
+            }
+            None => top_docs.into_iter().map(|top_doc| (top_doc, 0)).collect()
+        };
+        let results = top_docs.iter().skip(offset).filter_map(|((score, doc_address), collapsed_hits)| {
+            searcher.doc(*doc_address).ok().map(|retrieved| SearchResult{
+                score: *score,
+                collapsed_hits: *collapsed_hits,
+                retrievers: Vec::new(),
+                passage: None,
+                peer: String::new(),
+                relevant_url: get_text_field_value(&retrieved, url_field),
+                origin_url: get_text_field_value(&retrieved, origin_url_field),
+                depth: get_int_field_value(&retrieved, depth_field),
+                language: get_text_field_value(&retrieved, language_field),
+                title: get_text_field_value(&retrieved, title_field),
+                content_type: get_text_field_value(&retrieved, content_type_field),
+                fetched_at: retrieved.get_first(fetched_at_field)
+                    .and_then(|value| value.as_date())
+                    .map(|date| date.into_timestamp_secs())
+                    .unwrap_or_default()
+            })
+        }).collect();
+        Ok(SearchResponse {
+            status: ResponseStatus::Ok.into(),
+            message: None,
+            results,
+            facets,
+            total_hits: total_hits as u64,
+            suggestion: None,
+            failed_peers: Vec::new()
+        })
+    }
+
+    /// Searches the index, bypassing the result cache.
+    fn execute(&self, request: &SearchRequest) -> Result<SearchResponse, String> {
+        let searcher = ShardedSearcher::new(&self.shards);
+        let (offset, limit) = result_window(request.offset, request.limit)?;
+        let candidates = RETRIEVER_CANDIDATES.max(offset + limit);
+        match request.mode() {
+            SearchMode::Keyword => {}
+            SearchMode::Semantic => {
+                let query = self.scored_urls_query(self.semantic_candidates(&request.query, candidates));
+                let mut response = self.search(&searcher, query, request)?;
+                for result in &mut response.results {
+                    result.retrievers = vec![Retriever::Semantic.into()];
+                }
+                return Ok(response);
+            }
+            SearchMode::Hybrid => {
+                let fusion = FusionConfig::from_request(request.fusion.as_ref())?;
+                let keyword = self.keyword_candidates(&searcher, request, candidates)?;
+                let semantic = self.semantic_candidates(&request.query, candidates);
+                let mut fused = fusion.fuse(&keyword, &semantic);
+                let query = self.scored_urls_query(fused.iter().map(|(url, (score, _))| (url.clone(), *score)));
+                let mut response = self.search(&searcher, query, request)?;
+                for result in &mut response.results {
+                    if let Some((_, retrievers)) = fused.remove(&result.relevant_url) {
+                        result.retrievers = retrievers.into_iter().map(Into::into).collect();
                     }
                 }
-                Err(e) => println!("Failed to index {}. Error: {}", url, e)
+                return Ok(response);
             }
+        }
+        let (query, terms, mut passages) = if request.passages {
+            let passages = self.passages.as_ref()
+                .ok_or_else(|| "Passages are disabled".to_string())?
+                .search(&request.query, request.operator() == QueryOperator::And)?;
+            let query = self.scored_urls_query(passages.iter().map(|(url, passage)| (url.clone(), passage.score)));
+            (query, self.query_terms(&request.query), passages)
+        } else {
+            let (query, terms) = self.keyword_query(request)?;
+            (query, terms, HashMap::new())
+        };
+        let mut response = self.search(&searcher, query, request)?;
+        for result in &mut response.results {
+            result.retrievers = vec![Retriever::Keyword.into()];
+            result.passage = passages.remove(&result.relevant_url)
+                .map(|passage| Passage { text: passage.text, offset: passage.offset as u64 });
+        }
+        if response.total_hits < SUGGESTION_THRESHOLD {
+            let fields: Vec<Field> = DEFAULT_FIELD_BOOSTS.iter().map(|(name, _)| self.schema.get_field(name).unwrap()).collect();
+            let corrected: Vec<Option<String>> = terms.iter().map(|term| spelling::correct(&searcher, &fields, term)).collect();
+            response.suggestion = corrected.iter().any(Option::is_some).then(|| terms.iter().zip(corrected)
+                .map(|(term, correction)| correction.unwrap_or_else(|| term.clone()))
+                .collect::<Vec<_>>()
+                .join(" "));
+        }
+        Ok(response)
+    }
+}
+
+impl Writer for SearchEngine {
+    fn write(&self, page: &Page) -> Result<(), String> {
+        self.write_pages(std::slice::from_ref(page))
+    }
+}
+
+/// Offset and limit of the results of a search, checked so that the results up to the last one
+/// requested stay within `MAX_RESULT_WINDOW`.
+pub fn result_window(offset: u32, limit: Option<u32>) -> Result<(usize, usize), String> {
+    let (offset, limit) = (offset as usize, limit.unwrap_or(DEFAULT_LIMIT) as usize);
+    if limit == 0 {
+        return Err("Limit must be positive".to_string());
+    }
+    if offset + limit > MAX_RESULT_WINDOW {
+        return Err(format!("Offset plus limit must be at most {}", MAX_RESULT_WINDOW));
     }
+    Ok((offset, limit))
+}
+
+/// Boosts of the fields searched by default, by field name, overridden by the given boosts.
+pub fn field_boosts(overrides: &HashMap<String, Score>) -> Result<HashMap<String, Score>, String> {
+    let mut boosts: HashMap<String, Score> = DEFAULT_FIELD_BOOSTS.iter()
+        .map(|(name, boost)| (name.to_string(), *boost))
+        .collect();
+    for (name, boost) in overrides {
+        match boosts.get_mut(name) {
+            Some(default) => *default = *boost,
+            None => return Err(format!("Field {} is not searchable", name))
+        }
+    }
+    Ok(boosts)
+}
+
+/// Directory of a shard, within the index directory.
+fn shard_dir(shard: usize) -> String {
+    format!("shard-{}", shard)
+}
+
+fn shard_path(index_path: &Path, shard: usize) -> PathBuf {
+    index_path.join(shard_dir(shard))
 }
 
 fn get_text_field_value(doc: &Document, field: Field) -> String {
@@ -87,30 +975,99 @@ fn get_int_field_value(doc: &Document, field: Field) -> u32 {
 }
 
 impl Reader for SearchEngine {
-    fn read(&self, query: &str) -> Result<Vec<SearchResult>, String>{
-        let url_field = self.schema.get_field("url").unwrap();
-        let origin_url_field = self.schema.get_field("origin_url").unwrap();
-        let depth_field = self.schema.get_field("depth").unwrap();
+    fn read(&self, request: &SearchRequest) -> Result<SearchResponse, String>{
+        let generation = ShardedSearcher::new(&self.shards).generation();
+        let response = match self.cache.get(request, generation) {
+            Some(response) => response,
+            None => {
+                let response = self.execute(request)?;
+                self.cache.insert(request, generation, response.clone());
+                response
+            }
+        };
+        if response.total_hits > 0 {
+            self.query_log.record(&request.query);
+        }
+        Ok(response)
+    }
+
+    fn cache_stats(&self) -> CacheStats {
+        self.cache.stats()
+    }
+
+    fn suggest(&self, prefix: &str, limit: usize) -> Result<Vec<Suggestion>, String> {
+        let prefix = suggest::normalize(prefix);
+        if prefix.is_empty() {
+            return Ok(Vec::new());
+        }
+        let searcher = ShardedSearcher::new(&self.shards);
+        // Frequencies of the same text from several sources add up; the first source is kept.
+        let mut candidates: HashMap<String, (u64, SuggestionSource)> = HashMap::new();
+        let mut add = |text: String, frequency: u64, source: SuggestionSource| {
+            candidates.entry(text).or_insert((0, source)).0 += frequency;
+        };
+        for (query, count) in self.query_log.complete(&prefix) {
+            add(query, count, SuggestionSource::Query);
+        }
+        let title_suggest_field = self.schema.get_field("title_suggest").unwrap();
+        for (title, count) in suggest::complete_terms(&searcher, title_suggest_field, &prefix) {
+            add(title, count, SuggestionSource::Title);
+        }
+        // Only the last word of the prefix is completed from the term dictionary.
+        let (head, last_word) = match prefix.rsplit_once(' ') {
+            Some((head, last_word)) => (format!("{} ", head), last_word),
+            None => (String::new(), prefix.as_str())
+        };
+        for name in ["title", "body"] {
+            let field = self.schema.get_field(name).unwrap();
+            for (term, count) in suggest::complete_terms(&searcher, field, last_word) {
+                add(format!("{}{}", head, term), count, SuggestionSource::Term);
+            }
+        }
+        let mut suggestions: Vec<Suggestion> = candidates.into_iter()
+            .map(|(text, (frequency, source))| Suggestion { text, source: source.into(), frequency })
+            .collect();
+        suggestions.sort_by(|a, b| b.frequency.cmp(&a.frequency).then_with(|| a.text.cmp(&b.text)));
+        suggestions.truncate(limit);
+        Ok(suggestions)
+    }
+
+    fn more_like_this(&self, request: &MoreLikeThisRequest) -> Result<SearchResponse, String> {
+        let url_key_field = self.schema.get_field("url_key").unwrap();
         let body_field = self.schema.get_field("body").unwrap();
-        let searcher = self.reader.searcher();
-        let query_parser = QueryParser::for_index(&self.index, vec![body_field]);
-        let query = match query_parser.parse_query(query) {
-            Ok(r) => Ok(r),
-            Err(e) => Err(e.to_string())
-        }?;
-        let top_docs = match searcher.search(&query, &TopDocs::with_limit(10)) {
-            Ok(r) => Ok(r),
-            Err(e) => Err(e.to_string())
-        }?;
-        Ok(top_docs.iter().map(|(_score, doc_address)| {
-            if let Ok(retrieved) = searcher.doc(*doc_address) {
-                Ok(SearchResult{
-                    relevant_url: get_text_field_value(&retrieved, url_field),
-                    origin_url: get_text_field_value(&retrieved, origin_url_field),
-                    depth: get_int_field_value(&retrieved, depth_field)
-                })
-            } else {
-                return Err(())
-            }}).filter(|r| r.is_ok()).map(|r| r.unwrap()).collect())
+        let title_field = self.schema.get_field("title").unwrap();
+        let searcher = ShardedSearcher::new(&self.shards);
+        let (like, url) = match &request.like {
+            Some(Like::Url(url)) => {
+                let url_query = TermQuery::new(Term::from_field_text(url_key_field, url), IndexRecordOption::Basic);
+                let top_docs = searcher.search(&url_query, &TopDocs::with_limit(1)).map_err(|e| e.to_string())?;
+                let (_, doc_address) = top_docs.first().ok_or_else(|| format!("{} is not indexed", url))?;
+                let retrieved = searcher.doc(*doc_address).map_err(|e| e.to_string())?;
+                let like = [body_field, title_field].into_iter()
+                    .map(|field| (field, retrieved.get_all(field).cloned().collect()))
+                    .collect();
+                (like, Some(url))
+            }
+            Some(Like::Text(text)) => (vec![(body_field, vec![Value::Str(text.clone())])], None),
+            None => return Err("A URL or a text to find similar pages to is required".to_string())
+        };
+        // Every term counts, as small indexes have few documents sharing terms. Each shard picks the
+        // terms with its own statistics, while matches are scored with those of all shards.
+        let similar = MoreLikeThisQuery::builder()
+            .with_min_doc_frequency(1)
+            .with_min_term_frequency(1)
+            .with_document_fields(like);
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> = vec![(Occur::Must, Box::new(similar))];
+        if let Some(url) = url {
+            let url_query = TermQuery::new(Term::from_field_text(url_key_field, url), IndexRecordOption::Basic);
+            clauses.push((Occur::MustNot, Box::new(url_query)));
+        }
+        self.search(&searcher, Box::new(BooleanQuery::new(clauses)), &SearchRequest {
+            language: request.language.clone(),
+            filters: request.filters.clone(),
+            offset: request.offset,
+            limit: request.limit,
+            ..Default::default()
+        })
     }
 }
@@ -1,34 +1,92 @@
-use tonic::{Request, Response, Status};
+use std::collections::HashMap;
+use std::env;
+use std::io::{Seek, Write};
+use std::pin::Pin;
+use std::sync::Arc;
+
+use futures::channel::mpsc;
+use futures::Stream;
+use tonic::{Request, Response, Status, Streaming};
 use tonic::transport::Server;
 use tracing_subscriber::{filter, Layer};
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
 
+use cache::CacheConfig;
+use collection::{CollectionConfig, CrawlerConfig, DEFAULT_COLLECTION};
+use crawly::NoopObserver;
+use federation::{Federation, FederationConfig};
 use indexer::{Indexer, IndexerService};
-use search::{IndexRequest, IndexResponse, ResponseStatus, SearchRequest, SearchResponse};
+use progress::StreamingObserver;
+use passage::PassageConfig;
+use ranking::RankingConfig;
+use replication::{Replica, ReplicationConfig};
+use search::{CacheStats, CacheStatsRequest, CollectionResponse, CreateCollectionRequest, CrawlEvent, CrawlEventKind, DocumentChunk, DropCollectionRequest, ExportRequest, ImportResponse, IndexRequest, IndexResponse, ListCollectionsRequest, ListCollectionsResponse, MoreLikeThisRequest, ReplicateRequest, ReplicationEvent, ReplicationRole, ReplicationStatus, ReplicationStatusRequest, ResponseStatus, RestoreResponse, SearchRequest, SearchResponse, SnapshotChunk, SnapshotRequest, SuggestRequest, SuggestResponse};
 use search::searcher_server::{Searcher, SearcherServer};
 use search_engine::Reader;
+use shard::ShardConfig;
 
+mod cache;
+mod cjk;
+mod collapse;
+mod collection;
+mod embedding;
+mod export;
+mod expression;
+mod federation;
+mod fusion;
+mod hash;
+mod hnsw;
 mod indexer;
+mod language;
 mod search_engine;
-mod client;
+mod sort;
+mod spelling;
+mod suggest;
 mod crawly;
+mod link_graph;
+mod passage;
+mod progress;
+mod ranking;
+mod replication;
+mod shard;
+mod snapshot;
 
 mod search {
     include!("search.rs");
 }
 
+// Address the server listens on unless `SEARCH_ADDR` is set.
+const DEFAULT_ADDR: &str = "[::1]:50051";
+// Number of suggestions returned unless the request asks otherwise.
+const DEFAULT_SUGGESTION_LIMIT: u32 = 10;
+
 pub struct SearchService {
-    indexer: Box<IndexerService>,
+    indexer: Arc<IndexerService>,
+    federation: Federation,
+    // Set on followers, which replicate the collections of their leader instead of crawling.
+    replica: Option<Arc<Replica>>,
+}
+
+impl SearchService {
+    /// Rejects crawls on followers, whose index only changes through replication.
+    fn check_leader(&self) -> Result<(), String> {
+        match &self.replica {
+            Some(replica) => Err(format!("This server follows {}, which indexes its pages", replica.leader())),
+            None => Ok(())
+        }
+    }
 }
 
 #[tonic::async_trait]
 impl Searcher for SearchService {
     async fn index(&self, request: Request<IndexRequest>) -> Result<Response<IndexResponse>, Status> {
+        self.check_leader().map_err(Status::failed_precondition)?;
         let index_request = request.get_ref();
         let origin = &index_request.origin;
         let depth = &index_request.k;
-        match self.indexer.visit(origin, *depth).await {
+        let collection = self.indexer.collection(&index_request.collection).map_err(Status::not_found)?;
+        match collection.visit(origin, *depth, Arc::new(NoopObserver)).await {
             Ok(()) => Ok(Response::new(IndexResponse {
                 status: ResponseStatus::Ok.into(),
                 message: None
@@ -38,16 +96,184 @@ impl Searcher for SearchService {
     }
 
     async fn search(&self, request: Request<SearchRequest>) -> Result<Response<SearchResponse>, Status> {
-        let query =  &request.get_ref().query;
-        match self.indexer.read(query) {
-            Ok(results) => Ok(Response::new(SearchResponse {
+        let request = request.into_inner();
+        let collection = self.indexer.collection(&request.collection).map_err(Status::not_found)?;
+        let response = if request.local || self.federation.is_empty() {
+            collection.read(&request)
+        } else {
+            self.federation.search(&request, |request| collection.read(request)).await
+        };
+        match response {
+            Ok(response) => Ok(Response::new(response)),
+            Err(message) => Err(Status::aborted(message))
+        }
+    }
+
+    async fn suggest(&self, request: Request<SuggestRequest>) -> Result<Response<SuggestResponse>, Status> {
+        let suggest_request = request.get_ref();
+        let limit = suggest_request.limit.unwrap_or(DEFAULT_SUGGESTION_LIMIT) as usize;
+        let collection = self.indexer.collection(&suggest_request.collection).map_err(Status::not_found)?;
+        match collection.suggest(&suggest_request.prefix, limit) {
+            Ok(suggestions) => Ok(Response::new(SuggestResponse {
+                status: ResponseStatus::Ok.into(),
+                message: None,
+                suggestions
+            })),
+            Err(message) => Err(Status::aborted(message))
+        }
+    }
+
+    async fn more_like_this(&self, request: Request<MoreLikeThisRequest>) -> Result<Response<SearchResponse>, Status> {
+        let collection = self.indexer.collection(&request.get_ref().collection).map_err(Status::not_found)?;
+        match collection.more_like_this(request.get_ref()) {
+            Ok(response) => Ok(Response::new(response)),
+            Err(message) => Err(Status::aborted(message))
+        }
+    }
+
+    async fn get_cache_stats(&self, request: Request<CacheStatsRequest>) -> Result<Response<CacheStats>, Status> {
+        let collection = self.indexer.collection(&request.get_ref().collection).map_err(Status::not_found)?;
+        Ok(Response::new(collection.cache_stats()))
+    }
+
+    async fn create_collection(&self, request: Request<CreateCollectionRequest>) -> Result<Response<CollectionResponse>, Status> {
+        // Followers hold the collections of their leader.
+        self.check_leader().map_err(Status::failed_precondition)?;
+        let CreateCollectionRequest { name, options } = request.into_inner();
+        match self.indexer.create_collection(&name, &options.unwrap_or_default()) {
+            Ok(collection) => Ok(Response::new(CollectionResponse {
+                status: ResponseStatus::Ok.into(),
+                message: None,
+                collection: Some(collection.info())
+            })),
+            Err(message) => Err(Status::failed_precondition(message))
+        }
+    }
+
+    async fn list_collections(&self, _request: Request<ListCollectionsRequest>) -> Result<Response<ListCollectionsResponse>, Status> {
+        Ok(Response::new(ListCollectionsResponse {
+            collections: self.indexer.list_collections().iter().map(|collection| collection.info()).collect()
+        }))
+    }
+
+    async fn drop_collection(&self, request: Request<DropCollectionRequest>) -> Result<Response<CollectionResponse>, Status> {
+        self.check_leader().map_err(Status::failed_precondition)?;
+        match self.indexer.drop_collection(&request.get_ref().name) {
+            Ok(collection) => Ok(Response::new(CollectionResponse {
+                status: ResponseStatus::Ok.into(),
+                message: None,
+                collection: Some(collection.info())
+            })),
+            Err(message) => Err(Status::failed_precondition(message))
+        }
+    }
+
+    type ReplicateStream = Pin<Box<dyn Stream<Item = Result<ReplicationEvent, Status>> + Send>>;
+
+    async fn replicate(&self, request: Request<ReplicateRequest>) -> Result<Response<Self::ReplicateStream>, Status> {
+        let request = request.into_inner();
+        let collection = self.indexer.collection(&request.collection).map_err(Status::not_found)?;
+        let events = replication::stream(collection.search_engine().replication_log(), request)
+            .map_err(Status::out_of_range)?;
+        Ok(Response::new(Box::pin(events)))
+    }
+
+    async fn get_replication_status(&self, request: Request<ReplicationStatusRequest>) -> Result<Response<ReplicationStatus>, Status> {
+        let name = &request.get_ref().collection;
+        let collection = self.indexer.collection(name).map_err(Status::not_found)?;
+        let sequence = collection.search_engine().replication_log().sequence();
+        Ok(Response::new(match &self.replica {
+            Some(replica) => replica.status(if name.is_empty() { DEFAULT_COLLECTION } else { name }, sequence),
+            None => ReplicationStatus {
+                role: ReplicationRole::Leader.into(),
+                sequence,
+                leader_sequence: sequence,
+                ..Default::default()
+            }
+        }))
+    }
+
+    type SnapshotStream = Pin<Box<dyn Stream<Item = Result<SnapshotChunk, Status>> + Send>>;
+
+    async fn snapshot(&self, request: Request<SnapshotRequest>) -> Result<Response<Self::SnapshotStream>, Status> {
+        let collection = self.indexer.collection(&request.get_ref().collection).map_err(Status::not_found)?;
+        // Writes are only paused while the files are listed, the archive is then written to a
+        // temporary file and streamed from it.
+        let archive = tokio::task::spawn_blocking(move || {
+            let mut archive = tempfile::tempfile().map_err(|e| e.to_string())?;
+            let manifest = collection.snapshot(&mut archive)?;
+            archive.rewind().map_err(|e| e.to_string())?;
+            tracing::info!("Took snapshot at sequence {} of {} shards", manifest.sequence, manifest.shards);
+            Ok::<_, String>(archive)
+        }).await.map_err(|e| Status::internal(e.to_string()))?.map_err(Status::aborted)?;
+        Ok(Response::new(Box::pin(snapshot::stream(archive))))
+    }
+
+    async fn restore(&self, request: Request<Streaming<SnapshotChunk>>) -> Result<Response<RestoreResponse>, Status> {
+        self.check_leader().map_err(Status::failed_precondition)?;
+        let mut chunks = request.into_inner();
+        let first = chunks.message().await?.unwrap_or_default();
+        let collection = self.indexer.collection(&first.collection).map_err(Status::not_found)?;
+        let mut archive = tempfile::tempfile().map_err(|e| Status::internal(e.to_string()))?;
+        archive.write_all(&first.data).map_err(|e| Status::internal(e.to_string()))?;
+        while let Some(chunk) = chunks.message().await? {
+            archive.write_all(&chunk.data).map_err(|e| Status::internal(e.to_string()))?;
+        }
+        archive.rewind().map_err(|e| Status::internal(e.to_string()))?;
+        let manifest = tokio::task::spawn_blocking(move || collection.restore(archive))
+            .await.map_err(|e| Status::internal(e.to_string()))?
+            .map_err(Status::failed_precondition)?;
+        tracing::info!("Restored snapshot at sequence {} of {} shards", manifest.sequence, manifest.shards);
+        Ok(Response::new(RestoreResponse {
+            status: ResponseStatus::Ok.into(),
+            message: None,
+            sequence: manifest.sequence,
+            shards: manifest.shards as u32
+        }))
+    }
+
+    type ExportStream = Pin<Box<dyn Stream<Item = Result<DocumentChunk, Status>> + Send>>;
+
+    async fn export(&self, request: Request<ExportRequest>) -> Result<Response<Self::ExportStream>, Status> {
+        let collection = self.indexer.collection(&request.get_ref().collection).map_err(Status::not_found)?;
+        Ok(Response::new(Box::pin(export::stream(collection))))
+    }
+
+    async fn import(&self, request: Request<Streaming<DocumentChunk>>) -> Result<Response<ImportResponse>, Status> {
+        self.check_leader().map_err(Status::failed_precondition)?;
+        let mut chunks = request.into_inner();
+        let first = chunks.message().await?.unwrap_or_default();
+        let collection = self.indexer.collection(&first.collection).map_err(Status::not_found)?;
+        match export::import(collection, first, chunks).await {
+            Ok(pages) => Ok(Response::new(ImportResponse {
                 status: ResponseStatus::Ok.into(),
                 message: None,
-                results
+                pages
             })),
             Err(message) => Err(Status::aborted(message))
         }
     }
+
+    type IndexWithProgressStream = Pin<Box<dyn Stream<Item = Result<CrawlEvent, Status>> + Send>>;
+
+    async fn index_with_progress(&self, request: Request<IndexRequest>) -> Result<Response<Self::IndexWithProgressStream>, Status> {
+        self.check_leader().map_err(Status::failed_precondition)?;
+        let IndexRequest { origin, k, collection } = request.into_inner();
+        let collection = self.indexer.collection(&collection).map_err(Status::not_found)?;
+        let (sender, receiver) = mpsc::unbounded();
+        let observer = Arc::new(StreamingObserver::new(sender.clone()));
+        tokio::spawn(async move {
+            let last = match collection.visit(&origin, k, observer).await {
+                Ok(()) => progress::event(CrawlEventKind::Finished, &origin, 0),
+                Err(error) => CrawlEvent {
+                    message: Some(error.to_string()),
+                    ..progress::event(CrawlEventKind::Failed, &origin, 0)
+                }
+            };
+            let _ = sender.unbounded_send(Ok(last));
+        });
+        Ok(Response::new(Box::pin(receiver)))
+    }
 }
 
 #[tokio::main]
@@ -56,9 +282,24 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     tracing_subscriber::registry()
         .with(stdout_log.with_filter(filter::LevelFilter::INFO))
         .init();
-    let addr = "[::1]:50051".parse().unwrap();
+    // Federated servers run side by side, each on its own address.
+    let addr = env::var("SEARCH_ADDR").unwrap_or_else(|_| DEFAULT_ADDR.to_string()).parse()?;
+    let collections = CollectionConfig {
+        shards: ShardConfig::from_env()?,
+        passages: PassageConfig::from_env()?,
+        crawler: CrawlerConfig::default(),
+        field_boosts: HashMap::new()
+    };
+    let indexer = Arc::new(IndexerService::new(RankingConfig::from_env()?, CacheConfig::from_env()?, collections));
+    let replica = ReplicationConfig::from_env().leader.map(|leader| Arc::new(Replica::new(leader)));
+    if let Some(replica) = &replica {
+        println!("Following {}", replica.leader());
+        tokio::spawn(replica.clone().follow(indexer.clone()));
+    }
     let service = SearchService {
-        indexer: Box::new(IndexerService::default())
+        indexer,
+        federation: Federation::new(FederationConfig::from_env()?)?,
+        replica
     };
     println!("Search engine service listening on {}", addr);
     Server::builder()
@@ -0,0 +1,150 @@
+//! Shards of the index, each with its own writer, and the searcher gathering results over all of
+//! them as if they were one index.
+
+use std::env;
+use std::path::Path;
+use std::sync::Mutex;
+use std::thread;
+
+use tantivy::collector::Collector;
+use tantivy::query::{Bm25StatisticsProvider, EnableScoring, Query};
+use tantivy::schema::{Document, Field, Schema};
+use tantivy::{DocAddress, Index, IndexReader, IndexWriter, ReloadPolicy, Searcher, SegmentReader, Term};
+
+use crate::hash::fnv1a;
+use crate::language;
+
+/// Number of shards the pages are split across.
+#[derive(Clone, Debug)]
+pub struct ShardConfig {
+    pub shards: usize,
+}
+
+impl Default for ShardConfig {
+    fn default() -> Self {
+        Self { shards: 1 }
+    }
+}
+
+impl ShardConfig {
+    /// Default count, overridden by the `SEARCH_SHARDS` environment variable.
+    pub fn from_env() -> Result<Self, String> {
+        let config = match env::var("SEARCH_SHARDS") {
+            Ok(value) => Self { shards: value.parse().map_err(|_| format!("Invalid SEARCH_SHARDS: {}", value))? },
+            Err(_) => Self::default(),
+        };
+        if config.shards == 0 {
+            return Err("There must be at least one shard".to_string());
+        }
+        Ok(config)
+    }
+}
+
+/// Index of the shard holding the page with the given URL. The FNV-1a hash keeps the assignment
+/// the same across builds.
+pub fn shard_of(url: &str, shards: usize) -> usize {
+    let hash = fnv1a(url.as_bytes());
+    (hash % shards as u64) as usize
+}
+
+pub struct Shard {
+    pub index: Index,
+    // Wrapping it with a mutex allows IndexWriter to be mutable and used cross-thread.
+    pub index_writer: Mutex<IndexWriter>,
+    pub reader: IndexReader,
+}
+
+impl Shard {
+    pub fn create_in_dir(path: &Path, schema: Schema) -> Self {
+        std::fs::create_dir_all(path).expect("Unable to create shard dir");
+        Self::with_index(Index::create_in_dir(path, schema).expect("Unable to create index"))
+    }
+
+    /// Opens a shard written by another server, such as one restored from a snapshot.
+    pub fn open_in_dir(path: &Path) -> Result<Self, String> {
+        let index = Index::open_in_dir(path).map_err(|e| format!("Failed to open shard {}. Error: {}", path.display(), e))?;
+        Ok(Self::with_index(index))
+    }
+
+    fn with_index(index: Index) -> Self {
+        language::register_analyzers(&index);
+        let index_writer = index.writer(50_000_000).expect("Unable to create writer");
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::OnCommit)
+            .try_into().expect("Unable to create reader");
+        Self { index, index_writer: Mutex::new(index_writer), reader }
+    }
+}
+
+/// Searchers of all shards, taken at the same time. Segments are numbered across shards, in shard
+/// order, so that document addresses identify a document of any shard.
+pub struct ShardedSearcher {
+    searchers: Vec<Searcher>,
+    // Shard and ordinal within the shard of each segment.
+    segments: Vec<(usize, u32)>,
+}
+
+impl ShardedSearcher {
+    pub fn new(shards: &[Shard]) -> Self {
+        let searchers: Vec<Searcher> = shards.iter().map(|shard| shard.reader.searcher()).collect();
+        let segments = searchers.iter().enumerate()
+            .flat_map(|(shard, searcher)| (0..searcher.segment_readers().len() as u32).map(move |ord| (shard, ord)))
+            .collect();
+        Self { searchers, segments }
+    }
+
+    /// Runs the query on all shards in parallel and merges their results. Scores use the
+    /// statistics of all shards together, so they compare across shards.
+    pub fn search<C: Collector>(&self, query: &dyn Query, collector: &C) -> tantivy::Result<C::Fruit> {
+        let shard_fruits = thread::scope(|scope| {
+            let handles: Vec<_> = self.searchers.iter().enumerate().map(|(shard, searcher)| scope.spawn(move || {
+                let enable_scoring = if collector.requires_scoring() {
+                    EnableScoring::enabled_from_statistics_provider(self, searcher)
+                } else {
+                    EnableScoring::disabled_from_searcher(searcher)
+                };
+                let weight = query.weight(enable_scoring)?;
+                let first_segment = self.segments.iter().position(|(segment_shard, _)| *segment_shard == shard).unwrap_or_default();
+                searcher.segment_readers().iter().enumerate()
+                    .map(|(ord, segment_reader)| collector.collect_segment(weight.as_ref(), (first_segment + ord) as u32, segment_reader))
+                    .collect::<tantivy::Result<Vec<_>>>()
+            })).collect();
+            handles.into_iter().map(|handle| handle.join().expect("Shard search panicked")).collect::<tantivy::Result<Vec<_>>>()
+        })?;
+        collector.merge_fruits(shard_fruits.into_iter().flatten().collect())
+    }
+
+    pub fn doc(&self, doc_address: DocAddress) -> tantivy::Result<Document> {
+        let (shard, segment_ord) = self.segments[doc_address.segment_ord as usize];
+        self.searchers[shard].doc(DocAddress::new(segment_ord, doc_address.doc_id))
+    }
+
+    /// Readers of the segments of all shards, in the order of their ordinals.
+    pub fn segment_readers(&self) -> Vec<&SegmentReader> {
+        self.searchers.iter().flat_map(|searcher| searcher.segment_readers()).collect()
+    }
+
+    pub fn doc_freq(&self, term: &Term) -> tantivy::Result<u64> {
+        self.searchers.iter().map(|searcher| searcher.doc_freq(term)).sum()
+    }
+
+    /// Identifies the state of all shards: it changes whenever a shard's reader reloads.
+    pub fn generation(&self) -> u64 {
+        self.searchers.iter().map(|searcher| searcher.generation().generation_id()).sum()
+    }
+}
+
+impl Bm25StatisticsProvider for ShardedSearcher {
+    fn total_num_tokens(&self, field: Field) -> tantivy::Result<u64> {
+        self.searchers.iter().map(|searcher| Bm25StatisticsProvider::total_num_tokens(searcher, field)).sum()
+    }
+
+    fn total_num_docs(&self) -> tantivy::Result<u64> {
+        Ok(self.searchers.iter().map(|searcher| searcher.num_docs()).sum())
+    }
+
+    fn doc_freq(&self, term: &Term) -> tantivy::Result<u64> {
+        ShardedSearcher::doc_freq(self, term)
+    }
+}
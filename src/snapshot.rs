@@ -0,0 +1,151 @@
+//! Snapshots: point-in-time copies of the index directory and the replication log, stored as
+//! versioned tar archives.
+
+use std::fs::File;
+use std::io::{Cursor, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+
+use futures::channel::mpsc;
+use futures::SinkExt;
+use tantivy::directory::{Directory, META_LOCK};
+use tantivy::Index;
+use tonic::Status;
+
+use crate::search::SnapshotChunk;
+
+// Version of the archive layout, bumped whenever restoring older archives needs conversion.
+const FORMAT_VERSION: u32 = 1;
+// First entry of the archives, describing their content.
+const MANIFEST: &str = "MANIFEST";
+/// Size of the chunks snapshots and exports are streamed in.
+pub const CHUNK_SIZE: usize = 1024 * 1024;
+
+/// A file of an archive: its path within the index directory, its size and its content.
+pub type ArchivedFile<'a> = (PathBuf, u64, Box<dyn Read + 'a>);
+
+/// Description of a snapshot. Restored servers keep the shard count of the snapshot, which decides
+/// the shard of each page.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Manifest {
+    pub version: u32,
+    pub shards: usize,
+    // Sequence number of the last operation of the replication log.
+    pub sequence: u64,
+    // Seconds since the Unix epoch.
+    pub created_at: i64,
+}
+
+impl Manifest {
+    pub fn new(shards: usize, sequence: u64, created_at: i64) -> Self {
+        Self { version: FORMAT_VERSION, shards, sequence, created_at }
+    }
+
+    fn encode(&self) -> String {
+        format!("version={}\nshards={}\nsequence={}\ncreated_at={}\n", self.version, self.shards, self.sequence, self.created_at)
+    }
+
+    fn decode(text: &str) -> Result<Self, String> {
+        let value = |name: &str| -> Result<&str, String> {
+            text.lines()
+                .find_map(|line| line.strip_prefix(name).and_then(|rest| rest.strip_prefix('=')))
+                .ok_or_else(|| format!("Snapshot manifest has no {}", name))
+        };
+        let version = value("version")?.parse().map_err(|_| "Invalid snapshot version".to_string())?;
+        if version != FORMAT_VERSION {
+            return Err(format!("Unsupported snapshot version {}, expected {}", version, FORMAT_VERSION));
+        }
+        Ok(Self {
+            version,
+            shards: value("shards")?.parse().map_err(|_| "Invalid snapshot shard count".to_string())?,
+            sequence: value("sequence")?.parse().map_err(|_| "Invalid snapshot sequence".to_string())?,
+            created_at: value("created_at")?.parse().map_err(|_| "Invalid snapshot creation time".to_string())?,
+        })
+    }
+}
+
+/// The committed files of the index in the directory: its meta file and the files of its
+/// segments, by path within the directory, along with their size. The files stay readable after
+/// the index deletes them.
+pub fn committed_files(index: &Index, path: &Path) -> Result<Vec<ArchivedFile<'static>>, String> {
+    // The garbage collector takes the same lock, so no listed file is deleted before it is open.
+    let _meta_lock = index.directory().acquire_lock(&META_LOCK).map_err(|e| e.to_string())?;
+    let metas = index.load_metas().map_err(|e| e.to_string())?;
+    // The meta file is written from the metas loaded rather than copied, as merges replace it
+    // without taking the lock, with segments the archive would lack.
+    let mut meta = serde_json::to_vec_pretty(&metas).map_err(|e| e.to_string())?;
+    meta.push(b'\n');
+    let mut files: Vec<ArchivedFile> = vec![(PathBuf::from("meta.json"), meta.len() as u64, Box::new(Cursor::new(meta)))];
+    for name in metas.segments.iter().flat_map(|segment| segment.list_files()) {
+        let file = match File::open(path.join(&name)) {
+            // Segments list the files of all their components, including those they lack, such as
+            // deletes.
+            Err(e) if e.kind() == ErrorKind::NotFound => continue,
+            file => file.map_err(|e| format!("Failed to read {}. Error: {}", name.display(), e))?,
+        };
+        let size = file.metadata().map_err(|e| e.to_string())?.len();
+        files.push((name, size, Box::new(file)));
+    }
+    Ok(files)
+}
+
+/// Writes the archive: the manifest, then the files, each with its path within the index
+/// directory and its size.
+pub fn write_archive(manifest: &Manifest, files: Vec<ArchivedFile>, out: impl Write) -> Result<(), String> {
+    let mut builder = tar::Builder::new(out);
+    let mut append = |path: &Path, size: u64, content: &mut dyn Read| {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(size);
+        header.set_mode(0o644);
+        header.set_mtime(manifest.created_at.max(0) as u64);
+        builder.append_data(&mut header, path, content)
+            .map_err(|e| format!("Failed to archive {}. Error: {}", path.display(), e))
+    };
+    let encoded = manifest.encode();
+    append(Path::new(MANIFEST), encoded.len() as u64, &mut encoded.as_bytes())?;
+    for (path, size, mut content) in files {
+        append(&path, size, &mut content)?;
+    }
+    builder.into_inner().and_then(|mut out| out.flush()).map_err(|e| e.to_string())
+}
+
+/// Extracts the archive into the directory and returns its manifest.
+pub fn extract_archive(archive: impl Read, directory: &Path) -> Result<Manifest, String> {
+    let mut archive = tar::Archive::new(archive);
+    let mut entries = archive.entries().map_err(|e| e.to_string())?;
+    let mut first = entries.next()
+        .ok_or_else(|| "Snapshot archive is empty".to_string())?
+        .map_err(|e| e.to_string())?;
+    if first.path().map_err(|e| e.to_string())?.as_ref() != Path::new(MANIFEST) {
+        return Err("Snapshot archive does not start with a manifest".to_string());
+    }
+    let mut manifest = String::new();
+    first.read_to_string(&mut manifest).map_err(|e| e.to_string())?;
+    let manifest = Manifest::decode(&manifest)?;
+    for entry in entries {
+        let mut entry = entry.map_err(|e| e.to_string())?;
+        // Entries escaping the directory are skipped.
+        entry.unpack_in(directory).map_err(|e| format!("Failed to extract the snapshot. Error: {}", e))?;
+    }
+    Ok(manifest)
+}
+
+/// Streams the archive in chunks.
+pub fn stream(mut archive: File) -> mpsc::Receiver<Result<SnapshotChunk, Status>> {
+    let (mut sender, receiver) = mpsc::channel(1);
+    tokio::task::spawn_blocking(move || loop {
+        let mut data = vec![0; CHUNK_SIZE];
+        let chunk = match archive.read(&mut data) {
+            Ok(0) => return,
+            Ok(read) => {
+                data.truncate(read);
+                Ok(SnapshotChunk { data, ..Default::default() })
+            }
+            Err(e) => Err(Status::internal(e.to_string())),
+        };
+        let failed = chunk.is_err();
+        if futures::executor::block_on(sender.send(chunk)).is_err() || failed {
+            return;
+        }
+    });
+    receiver
+}
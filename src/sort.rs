@@ -0,0 +1,57 @@
+//! Sort keys of search results, read from fast fields.
+
+use std::cmp::Reverse;
+
+use tantivy::{DocId, Score, SegmentReader};
+
+use crate::search::{SearchResult, SortBy};
+
+/// Value a result is sorted by. Results with greater values come first, so orders that put
+/// smaller field values first wrap them in `Reverse`.
+#[derive(Clone, PartialEq, PartialOrd)]
+pub enum SortValue {
+    Score(Score),
+    FetchedAt(i64),
+    Depth(Reverse<u64>),
+    Url(Reverse<String>),
+}
+
+/// Builds the function computing the sort value of the documents of a segment from their score.
+pub fn sort_value_reader(sort_by: SortBy, segment_reader: &SegmentReader) -> Box<dyn Fn(DocId, Score) -> SortValue> {
+    let fast_fields = segment_reader.fast_fields();
+    match sort_by {
+        SortBy::Relevance => Box::new(|_doc, score| SortValue::Score(score)),
+        SortBy::FetchedAt => {
+            let fetched_at = fast_fields.date("fetched_at").unwrap();
+            Box::new(move |doc, _score| SortValue::FetchedAt(
+                fetched_at.first(doc).map(|date| date.into_timestamp_secs()).unwrap_or(i64::MIN)
+            ))
+        }
+        SortBy::Depth => {
+            let depth = fast_fields.u64("depth").unwrap();
+            Box::new(move |doc, _score| SortValue::Depth(Reverse(depth.first(doc).unwrap_or(u64::MAX))))
+        }
+        SortBy::Url => {
+            let url = fast_fields.str("url_key").unwrap();
+            Box::new(move |doc, _score| {
+                let mut value = String::new();
+                if let Some(url) = &url {
+                    if let Some(ord) = url.term_ords(doc).next() {
+                        let _ = url.ord_to_str(ord, &mut value);
+                    }
+                }
+                SortValue::Url(Reverse(value))
+            })
+        }
+    }
+}
+
+/// Sort value of a returned result, for merging results returned by several searches.
+pub fn result_sort_value(sort_by: SortBy, result: &SearchResult) -> SortValue {
+    match sort_by {
+        SortBy::Relevance => SortValue::Score(result.score),
+        SortBy::FetchedAt => SortValue::FetchedAt(result.fetched_at),
+        SortBy::Depth => SortValue::Depth(Reverse(result.depth as u64)),
+        SortBy::Url => SortValue::Url(Reverse(result.relevant_url.clone())),
+    }
+}
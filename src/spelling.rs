@@ -0,0 +1,85 @@
+//! Typo tolerance: edit distances scaled to term length and spelling suggestions drawn from the
+//! term dictionary of the index.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use levenshtein_automata::{Distance, LevenshteinAutomatonBuilder, DFA};
+use tantivy::schema::Field;
+use tantivy::Term;
+use tantivy_fst::Automaton;
+
+use crate::shard::ShardedSearcher;
+
+// Upper bound on the dictionary entries examined per field and segment, to bound latency.
+const MAX_SCANNED_TERMS: usize = 1_000;
+
+/// Number of edits tolerated in a term of the given length in characters.
+pub fn max_edits(term: &str) -> u8 {
+    match term.chars().count() {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    }
+}
+
+// Builders of the automata matching the terms within one and two edits, costly to create.
+static AUTOMATON_BUILDERS: [OnceLock<LevenshteinAutomatonBuilder>; 2] = [OnceLock::new(), OnceLock::new()];
+
+/// Matches the terms within an edit distance, transpositions of adjacent characters counting as
+/// one edit, so that the term dictionary streams only the candidates.
+struct EditDistance(DFA);
+
+impl Automaton for EditDistance {
+    type State = u32;
+
+    fn start(&self) -> u32 {
+        self.0.initial_state()
+    }
+
+    fn is_match(&self, state: &u32) -> bool {
+        matches!(self.0.distance(*state), Distance::Exact(_))
+    }
+
+    fn can_match(&self, state: &u32) -> bool {
+        *state != levenshtein_automata::SINK_STATE
+    }
+
+    fn accept(&self, state: &u32, byte: u8) -> u32 {
+        self.0.transition(*state, byte)
+    }
+}
+
+/// Finds the most frequent term of the fields within the tolerated edit distance of `term`.
+/// Returns `None` if no candidate is more frequent than the term itself.
+pub fn correct(searcher: &ShardedSearcher, fields: &[Field], term: &str) -> Option<String> {
+    let edits = max_edits(term);
+    if edits == 0 {
+        return None;
+    }
+    let builder = AUTOMATON_BUILDERS[edits as usize - 1]
+        .get_or_init(|| LevenshteinAutomatonBuilder::new(edits, true));
+    let mut frequencies: HashMap<String, u64> = HashMap::new();
+    for field in fields {
+        for segment_reader in searcher.segment_readers() {
+            let Ok(inverted_index) = segment_reader.inverted_index(*field) else { continue };
+            let automaton = EditDistance(builder.build_dfa(term));
+            let Ok(mut stream) = inverted_index.terms().search(automaton).into_stream() else { continue };
+            let mut scanned = 0;
+            while scanned < MAX_SCANNED_TERMS {
+                let Some((key, info)) = stream.next() else { break };
+                scanned += 1;
+                if let Ok(candidate) = std::str::from_utf8(key) {
+                    *frequencies.entry(candidate.to_string()).or_default() += info.doc_freq as u64;
+                }
+            }
+        }
+    }
+    let original = fields.iter()
+        .map(|field| searcher.doc_freq(&Term::from_field_text(*field, term)).unwrap_or_default())
+        .sum::<u64>();
+    frequencies.into_iter()
+        .filter(|(candidate, frequency)| candidate != term && *frequency > original)
+        .max_by(|(a, a_frequency), (b, b_frequency)| a_frequency.cmp(b_frequency).then(b.cmp(a)))
+        .map(|(candidate, _)| candidate)
+}
@@ -0,0 +1,71 @@
+//! Query completion from the term dictionary, indexed titles and past queries.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use tantivy::schema::Field;
+
+use crate::shard::ShardedSearcher;
+
+// Upper bound on the dictionary entries examined per field and segment, to bound latency.
+const MAX_SCANNED_TERMS: usize = 1_000;
+// Upper bound on the distinct queries remembered.
+const MAX_LOGGED_QUERIES: usize = 10_000;
+
+/// Counts of past queries, normalized to lowercase.
+#[derive(Default)]
+pub struct QueryLog {
+    counts: Mutex<HashMap<String, u64>>,
+}
+
+impl QueryLog {
+    pub fn record(&self, query: &str) {
+        let query = normalize(query);
+        if query.is_empty() {
+            return;
+        }
+        let mut counts = self.counts.lock().unwrap();
+        if counts.len() >= MAX_LOGGED_QUERIES && !counts.contains_key(&query) {
+            // Make room by forgetting the queries asked only once.
+            counts.retain(|_, count| *count > 1);
+            if counts.len() >= MAX_LOGGED_QUERIES {
+                return;
+            }
+        }
+        *counts.entry(query).or_default() += 1;
+    }
+
+    /// Past queries starting with the prefix, with the number of times they were asked.
+    pub fn complete(&self, prefix: &str) -> Vec<(String, u64)> {
+        let prefix = normalize(prefix);
+        self.counts.lock().unwrap().iter()
+            .filter(|(query, _)| query.starts_with(&prefix))
+            .map(|(query, count)| (query.clone(), *count))
+            .collect()
+    }
+}
+
+/// Lowercases and collapses whitespace.
+pub fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// Terms of the field starting with the prefix, with the number of documents containing them.
+pub fn complete_terms(searcher: &ShardedSearcher, field: Field, prefix: &str) -> Vec<(String, u64)> {
+    let mut frequencies: HashMap<String, u64> = HashMap::new();
+    for segment_reader in searcher.segment_readers() {
+        let Ok(inverted_index) = segment_reader.inverted_index(field) else { continue };
+        let Ok(mut stream) = inverted_index.terms().range().ge(prefix).into_stream() else { continue };
+        let mut scanned = 0;
+        while let Some((key, info)) = stream.next() {
+            if !key.starts_with(prefix.as_bytes()) || scanned == MAX_SCANNED_TERMS {
+                break;
+            }
+            scanned += 1;
+            if let Ok(term) = std::str::from_utf8(key) {
+                *frequencies.entry(term.to_string()).or_default() += info.doc_freq as u64;
+            }
+        }
+    }
+    frequencies.into_iter().collect()
+}